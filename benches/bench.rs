@@ -123,6 +123,21 @@ fn renders(criterion: &mut Criterion) {
         );
     });
 
+    group.bench_function("example-items-by-reference", |bencher| {
+        let items = example_items();
+        let tree = Tree::new(&items).unwrap();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        bencher.iter_batched(
+            || Buffer::empty(buffer_size),
+            |mut buffer| {
+                black_box(&tree).render(buffer_size, black_box(&mut buffer), black_box(&mut state));
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
     group.finish();
 }
 
@@ -0,0 +1,102 @@
+use ratatui::text::{Line, Span};
+
+use crate::tree_item::TreeItem;
+
+/// Create a breadcrumb [`Line`] for the given identifier path like `Bravo › Delta › Echo`.
+///
+/// Each segment is the first line of the text of the [`TreeItem`] on the path, keeping its style.
+/// The segments are joined with the `separator`.
+///
+/// Returns `None` when the path is empty or does not exist in the `items` (anymore).
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::{breadcrumb, TreeItem, TreeState};
+/// let items = vec![TreeItem::new("r", "Root", vec![TreeItem::new_leaf("l", "Leaf")])?];
+/// let mut state = TreeState::default();
+/// state.select(vec!["r", "l"]);
+///
+/// let line = breadcrumb(&items, state.selected(), " › ").unwrap();
+/// assert_eq!(line.to_string(), "Root › Leaf");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[must_use]
+pub fn breadcrumb<'a, Identifier>(
+    items: &'a [TreeItem<'a, Identifier>],
+    identifier: &[Identifier],
+    separator: &str,
+) -> Option<Line<'a>>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    if identifier.is_empty() {
+        return None;
+    }
+
+    let mut spans = Vec::new();
+    let mut current = items;
+    for (index, part) in identifier.iter().enumerate() {
+        let item = current.iter().find(|item| item.identifier == *part)?;
+        if index > 0 {
+            spans.push(Span::raw(separator.to_owned()));
+        }
+        if let Some(line) = item.text.lines.first() {
+            let line_style = item.text.style.patch(line.style);
+            spans.extend(
+                line.spans
+                    .iter()
+                    .map(|span| Span::styled(span.content.as_ref(), line_style.patch(span.style))),
+            );
+        }
+        current = &item.children;
+    }
+    Some(Line::from(spans))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Style};
+
+    use super::*;
+
+    #[test]
+    fn deep_path() {
+        let items = TreeItem::example();
+        let line = breadcrumb(&items, &["b", "d", "f"], " › ").unwrap();
+        assert_eq!(line.to_string(), "Bravo › Delta › Foxtrot");
+    }
+
+    #[test]
+    fn keeps_style() {
+        let style = Style::new().fg(Color::Red);
+        let items = vec![TreeItem::new(
+            "r",
+            Span::styled("Root", style),
+            vec![TreeItem::new_leaf("l", "Leaf")],
+        )
+        .unwrap()];
+        let line = breadcrumb(&items, &["r", "l"], "/").unwrap();
+        assert_eq!(
+            line.spans,
+            [
+                Span::styled("Root", style),
+                Span::raw("/"),
+                Span::raw("Leaf")
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_path_is_none() {
+        let items = TreeItem::example();
+        assert_eq!(breadcrumb(&items, &["b", "x", "e"], " › "), None);
+        assert_eq!(breadcrumb(&items, &["a", "b"], " › "), None);
+    }
+
+    #[test]
+    fn empty_path_is_none() {
+        let items = TreeItem::example();
+        assert_eq!(breadcrumb(&items, &[], " › "), None);
+    }
+}
@@ -0,0 +1,270 @@
+use ratatui::layout::Position;
+
+use crate::path_set::PathSet;
+use crate::tree_item::TreeItem;
+use crate::tree_state::{Enter, TreeState};
+
+/// User input queued with [`TreeState::push_event`] and applied with [`TreeState::drain_events`].
+///
+/// Events can be created where the input is handled, for example on another thread, and sent to where the [`TreeState`] is rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEvent<Identifier> {
+    /// See [`TreeState::key_up`]
+    Up,
+    /// See [`TreeState::key_down`]
+    Down,
+    /// See [`TreeState::key_left`]
+    Left,
+    /// See [`TreeState::key_right`]
+    Right,
+    /// See [`TreeState::key_page_up`]
+    PageUp,
+    /// See [`TreeState::key_page_down`]
+    PageDown,
+    /// See [`TreeState::select_first`]
+    First,
+    /// See [`TreeState::select_last`]
+    Last,
+    /// See [`TreeState::toggle_selected_item`]
+    Toggle,
+    /// See [`TreeState::enter`]
+    Enter,
+    /// See [`TreeState::select`]
+    SelectPath(Vec<Identifier>),
+    /// See [`TreeState::click_at`]
+    Click(Position),
+    /// See [`TreeState::scroll_up`]
+    ScrollUp(usize),
+    /// See [`TreeState::scroll_down`]
+    ScrollDown(usize),
+}
+
+/// Observable result of a [`TreeEvent`] returned by [`TreeState::drain_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEffect<Identifier> {
+    /// Contains the now selected identifier
+    SelectionChanged(Vec<Identifier>),
+    /// The node was opened
+    Opened(Vec<Identifier>),
+    /// The node was closed
+    Closed(Vec<Identifier>),
+    /// A [`TreeItem`] was activated, see [`Enter::Activated`]
+    Activated(Vec<Identifier>),
+    /// A [lazy](TreeItem::new_lazy) node was opened and needs its children to be loaded
+    NeedsChildren(Vec<Identifier>),
+    /// The event refers to an item which does not exist (anymore) so nothing happened
+    Ignored(TreeEvent<Identifier>),
+}
+
+impl<Identifier> TreeState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Queue an event to be applied with the next [`drain_events`](Self::drain_events).
+    pub fn push_event(&mut self, event: TreeEvent<Identifier>) {
+        self.events.push_back(event);
+    }
+
+    /// Apply all [queued](Self::push_event) events in order.
+    ///
+    /// Call this right before rendering as events like [`TreeEvent::Down`] are based on the last render.
+    /// Events without an observable effect are not part of the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{TreeEffect, TreeEvent, TreeItem, TreeState};
+    /// let items = vec![TreeItem::new("b", "Bravo", vec![TreeItem::new_leaf("c", "Charlie")])?];
+    /// let mut state = TreeState::default();
+    /// state.push_event(TreeEvent::SelectPath(vec!["b"]));
+    /// state.push_event(TreeEvent::Toggle);
+    /// state.push_event(TreeEvent::SelectPath(vec!["gone"]));
+    /// assert_eq!(
+    ///     state.drain_events(&items),
+    ///     [
+    ///         TreeEffect::SelectionChanged(vec!["b"]),
+    ///         TreeEffect::Opened(vec!["b"]),
+    ///         TreeEffect::Ignored(TreeEvent::SelectPath(vec!["gone"])),
+    ///     ]
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn drain_events(&mut self, items: &[TreeItem<Identifier>]) -> Vec<TreeEffect<Identifier>> {
+        let mut effects = Vec::new();
+        while let Some(event) = self.events.pop_front() {
+            self.apply_event(items, event, &mut effects);
+        }
+        effects
+    }
+
+    fn apply_event(
+        &mut self,
+        items: &[TreeItem<Identifier>],
+        event: TreeEvent<Identifier>,
+        effects: &mut Vec<TreeEffect<Identifier>>,
+    ) {
+        let exists = |identifier: &[Identifier]| {
+            TreeItem::find(items, identifier).is_some()
+                || TreeItem::find_show_more_parent(items, identifier).is_some()
+        };
+        let stale = match &event {
+            TreeEvent::Left | TreeEvent::Right | TreeEvent::Toggle | TreeEvent::Enter => {
+                !self.selected.is_empty()
+                    && self.selected_bookmark.is_none()
+                    && !exists(&self.selected)
+            }
+            TreeEvent::SelectPath(identifier) => !identifier.is_empty() && !exists(identifier),
+            TreeEvent::Click(position) => self
+                .rendered_at(*position)
+                .is_none_or(|identifier| !exists(identifier)),
+            _ => false,
+        };
+        if stale {
+            effects.push(TreeEffect::Ignored(event));
+            return;
+        }
+
+        let selected_before = self.selected.clone();
+        let may_open = matches!(
+            event,
+            TreeEvent::Left
+                | TreeEvent::Right
+                | TreeEvent::Toggle
+                | TreeEvent::Enter
+                | TreeEvent::Click(_)
+        );
+        let opened_before = may_open.then(|| self.opened.clone());
+
+        let mut activated = None;
+        match event {
+            TreeEvent::Up => _ = self.key_up(),
+            TreeEvent::Down => _ = self.key_down(),
+            TreeEvent::Left => _ = self.key_left(),
+            TreeEvent::Right => _ = self.key_right(),
+            TreeEvent::PageUp => _ = self.key_page_up(),
+            TreeEvent::PageDown => _ = self.key_page_down(),
+            TreeEvent::First => _ = self.select_first(),
+            TreeEvent::Last => _ = self.select_last(),
+            TreeEvent::Toggle => _ = self.toggle_selected_item(items),
+            TreeEvent::Enter => {
+                if let Enter::Activated(identifier) = self.enter(items) {
+                    activated = Some(identifier);
+                }
+            }
+            TreeEvent::SelectPath(identifier) => _ = self.select(identifier),
+            TreeEvent::Click(position) => _ = self.click_at(position),
+            TreeEvent::ScrollUp(lines) => _ = self.scroll_up(lines),
+            TreeEvent::ScrollDown(lines) => _ = self.scroll_down(lines),
+        }
+
+        if self.selected != selected_before {
+            effects.push(TreeEffect::SelectionChanged(self.selected.clone()));
+        }
+        if let Some(opened_before) = opened_before {
+            self.push_open_effects(items, &opened_before, effects);
+        }
+        if let Some(identifier) = activated {
+            effects.push(TreeEffect::Activated(identifier));
+        }
+    }
+
+    /// Closed and opened nodes compared to before, parents first.
+    fn push_open_effects(
+        &self,
+        items: &[TreeItem<Identifier>],
+        opened_before: &PathSet<Identifier>,
+        effects: &mut Vec<TreeEffect<Identifier>>,
+    ) {
+        let mut closed = opened_before
+            .iter()
+            .filter(|identifier| !self.opened.contains(identifier))
+            .collect::<Vec<_>>();
+        closed.sort_by_key(Vec::len);
+        effects.extend(closed.into_iter().map(TreeEffect::Closed));
+
+        let mut opened = self
+            .opened
+            .iter()
+            .filter(|identifier| !opened_before.contains(identifier))
+            .collect::<Vec<_>>();
+        opened.sort_by_key(Vec::len);
+        for identifier in opened {
+            let needs_children = TreeItem::find(items, &identifier).is_some_and(|item| {
+                item.lazy && item.children.is_empty() && !self.loading.contains(&identifier)
+            });
+            effects.push(TreeEffect::Opened(identifier.clone()));
+            if needs_children {
+                effects.push(TreeEffect::NeedsChildren(identifier));
+            }
+        }
+    }
+}
+
+#[test]
+fn drain_mixed_batch() {
+    let mut items = TreeItem::example();
+    items.push(TreeItem::new_lazy("i", "India"));
+    let mut state = TreeState::default();
+    state.open(vec!["b"]);
+    let area = ratatui::layout::Rect::new(0, 0, 10, 10);
+    let mut buffer = ratatui::buffer::Buffer::empty(area);
+    ratatui::widgets::StatefulWidget::render(
+        crate::Tree::new(&items).unwrap(),
+        area,
+        &mut buffer,
+        &mut state,
+    );
+
+    for event in [
+        TreeEvent::Down,
+        TreeEvent::Down,
+        TreeEvent::Down,
+        TreeEvent::Down,
+        TreeEvent::Right,
+        TreeEvent::ScrollDown(1),
+        TreeEvent::SelectPath(vec!["b", "x"]),
+        TreeEvent::Click(Position::new(20, 0)),
+        TreeEvent::Click(Position::new(0, 6)),
+        TreeEvent::Toggle,
+        TreeEvent::Enter,
+        TreeEvent::SelectPath(vec!["b"]),
+        TreeEvent::Left,
+    ] {
+        state.push_event(event);
+    }
+    assert_eq!(
+        state.drain_events(&items),
+        [
+            TreeEffect::SelectionChanged(vec!["a"]),
+            TreeEffect::SelectionChanged(vec!["b"]),
+            TreeEffect::SelectionChanged(vec!["b", "c"]),
+            TreeEffect::SelectionChanged(vec!["b", "d"]),
+            TreeEffect::Opened(vec!["b", "d"]),
+            TreeEffect::Ignored(TreeEvent::SelectPath(vec!["b", "x"])),
+            TreeEffect::Ignored(TreeEvent::Click(Position::new(20, 0))),
+            TreeEffect::SelectionChanged(vec!["i"]),
+            TreeEffect::Opened(vec!["i"]),
+            TreeEffect::NeedsChildren(vec!["i"]),
+            TreeEffect::Closed(vec!["i"]),
+            TreeEffect::SelectionChanged(vec!["b"]),
+            TreeEffect::Closed(vec!["b"]),
+        ]
+    );
+    assert!(state.drain_events(&items).is_empty());
+    assert_eq!(state.selected(), ["b"]);
+    assert_eq!(state.get_offset(), 1);
+    assert_eq!(state.opened().len(), 1);
+    assert!(state.opened().contains(&["b", "d"]));
+}
+
+#[test]
+fn drain_activates_leaf() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.select(vec!["a"]);
+    state.push_event(TreeEvent::Enter);
+    assert_eq!(
+        state.drain_events(&items),
+        [TreeEffect::Activated(vec!["a"])]
+    );
+}
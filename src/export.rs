@@ -0,0 +1,357 @@
+//! Export [`TreeItem`]s into other formats.
+
+use std::fmt::{Display, Write};
+
+use crate::flatten::shown_children;
+use crate::tree_item::TreeItem;
+use crate::tree_state::TreeState;
+
+/// Direction of the graph layout in [`to_dot`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RankDir {
+    /// Parents above their children
+    #[default]
+    TopBottom,
+    /// Parents left of their children
+    LeftRight,
+    /// Parents below their children
+    BottomTop,
+    /// Parents right of their children
+    RightLeft,
+}
+
+impl RankDir {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::TopBottom => "TB",
+            Self::LeftRight => "LR",
+            Self::BottomTop => "BT",
+            Self::RightLeft => "RL",
+        }
+    }
+}
+
+/// Options for [`to_dot`].
+#[must_use]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DotOptions {
+    identifiers: bool,
+    rankdir: RankDir,
+    max_depth: Option<usize>,
+}
+
+impl DotOptions {
+    pub const fn new() -> Self {
+        Self {
+            identifiers: false,
+            rankdir: RankDir::TopBottom,
+            max_depth: None,
+        }
+    }
+
+    /// Include the identifier of each [`TreeItem`] in its label.
+    pub const fn identifiers(mut self, identifiers: bool) -> Self {
+        self.identifiers = identifiers;
+        self
+    }
+
+    pub const fn rankdir(mut self, rankdir: RankDir) -> Self {
+        self.rankdir = rankdir;
+        self
+    }
+
+    /// Only export [`TreeItem`]s up to the given zero based depth.
+    /// Depth 0 means only the top level items are exported.
+    pub const fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// Export the [`TreeItem`]s as a [Graphviz](https://graphviz.org/) DOT digraph.
+///
+/// Every item is a node labeled with its text and connected to its children.
+/// The node IDs are based on the index path of the item so they are unique even when texts or identifiers repeat.
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::export::{to_dot, DotOptions};
+/// # use tui_tree_widget::TreeItem;
+/// let items = vec![TreeItem::new("r", "Root", vec![TreeItem::new_leaf("l", "Leaf")])?];
+/// let dot = to_dot(&items, DotOptions::new());
+/// assert!(dot.contains(r#""n0" -> "n0_0";"#));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[must_use]
+pub fn to_dot<Identifier>(items: &[TreeItem<Identifier>], options: DotOptions) -> String
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash + Display,
+{
+    let mut result = String::from("digraph {\n");
+    _ = writeln!(result, "    rankdir={};", options.rankdir.as_str());
+    write_nodes(&mut result, items, None, "n", 0, options);
+    result += "}\n";
+    result
+}
+
+fn write_nodes<Identifier>(
+    result: &mut String,
+    items: &[TreeItem<Identifier>],
+    parent: Option<&str>,
+    prefix: &str,
+    depth: usize,
+    options: DotOptions,
+) where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash + Display,
+{
+    if options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return;
+    }
+
+    for (index, item) in items.iter().enumerate() {
+        let node = format!("{prefix}{index}");
+
+        let mut label = item
+            .text
+            .lines
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if options.identifiers {
+            _ = write!(label, " ({})", item.identifier);
+        }
+        _ = writeln!(result, "    \"{node}\" [label=\"{}\"];", escape(&label));
+
+        if let Some(parent) = parent {
+            _ = writeln!(result, "    \"{parent}\" -> \"{node}\";");
+        }
+
+        write_nodes(
+            result,
+            &item.children,
+            Some(&node),
+            &format!("{node}_"),
+            depth + 1,
+            options,
+        );
+    }
+}
+
+/// Escape the text to be used inside a quoted DOT string
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\r' => {}
+            _ => result.push(char),
+        }
+    }
+    result
+}
+
+/// A currently viewable [`TreeItem`] returned by [`visible_entries`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibleEntry<Identifier> {
+    /// Identifier of the item including the identifiers of its parents
+    pub identifier: Vec<Identifier>,
+    /// Zero based depth. Depth 0 means top level with 0 indentation.
+    pub depth: usize,
+    /// Text of the item without styling. Multiple lines are separated by `\n`.
+    pub text: String,
+    /// The item is open and its children are visible
+    pub open: bool,
+    /// The item has no children and is not [lazy](TreeItem::new_lazy)
+    pub leaf: bool,
+}
+
+/// Get all currently viewable (including by scrolling) [`TreeItem`]s in the order they are rendered.
+///
+/// When `under` is given only that item and its viewable descendants are returned.
+/// The depth stays relative to the top level items.
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::export::visible_entries;
+/// # use tui_tree_widget::{TreeItem, TreeState};
+/// let items = vec![TreeItem::new("r", "Root", vec![TreeItem::new_leaf("l", "Leaf")])?];
+/// let mut state = TreeState::default();
+/// state.open(vec!["r"]);
+/// let entries = visible_entries(&items, &state, None);
+/// assert_eq!(entries[1].identifier, ["r", "l"]);
+/// assert_eq!(entries[1].text, "Leaf");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[must_use]
+pub fn visible_entries<Identifier>(
+    items: &[TreeItem<Identifier>],
+    state: &TreeState<Identifier>,
+    under: Option<&[Identifier]>,
+) -> Vec<VisibleEntry<Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    state
+        .flatten(items)
+        .into_iter()
+        .filter(|flattened| under.is_none_or(|under| flattened.identifier.starts_with(under)))
+        .map(|flattened| {
+            let item = flattened.item;
+            let text = if let Some(parent) =
+                TreeItem::find_show_more_parent(items, &flattened.identifier)
+            {
+                let parent_identifier = &flattened.identifier[..flattened.identifier.len() - 1];
+                let hidden =
+                    parent.children.len() - shown_children(&state.pages, parent_identifier, parent);
+                format!("… show {hidden} more")
+            } else {
+                item.dynamic_text()
+                    .as_ref()
+                    .unwrap_or(&item.text)
+                    .lines
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let leaf = item.children.is_empty() && !item.lazy;
+            VisibleEntry {
+                depth: flattened.depth(),
+                open: !leaf
+                    && ((item.header && flattened.depth() == 0)
+                        || state.opened.contains(&flattened.identifier)),
+                leaf,
+                identifier: flattened.identifier.to_vec(),
+                text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_and_nodes() {
+        let dot = to_dot(&TreeItem::example(), DotOptions::new());
+        assert!(dot.starts_with("digraph {\n    rankdir=TB;\n"));
+        assert_eq!(dot.matches("->").count(), 5);
+        assert_eq!(dot.matches("[label=").count(), 8);
+        assert!(dot.contains(r#""n1_1" [label="Delta"];"#));
+        assert!(dot.contains(r#""n1_1" -> "n1_1_0";"#));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let items = vec![
+            TreeItem::new_leaf("q", r#"say "hi""#),
+            TreeItem::new_leaf("n", "two\nlines"),
+        ];
+        let dot = to_dot(&items, DotOptions::new());
+        assert!(dot.contains(r#"[label="say \"hi\""];"#));
+        assert!(dot.contains(r#"[label="two\nlines"];"#));
+    }
+
+    #[test]
+    fn same_texts_are_different_nodes() {
+        let items = vec![
+            TreeItem::new("a", "Same", vec![TreeItem::new_leaf("c", "Child")]).unwrap(),
+            TreeItem::new("b", "Same", vec![TreeItem::new_leaf("c", "Child")]).unwrap(),
+        ];
+        let dot = to_dot(&items, DotOptions::new());
+        assert!(dot.contains(r#""n0" -> "n0_0";"#));
+        assert!(dot.contains(r#""n1" -> "n1_0";"#));
+    }
+
+    #[test]
+    fn options() {
+        let options = DotOptions::new()
+            .identifiers(true)
+            .rankdir(RankDir::LeftRight)
+            .max_depth(Some(1));
+        let dot = to_dot(&TreeItem::example(), options);
+        assert!(dot.contains("rankdir=LR;"));
+        assert!(dot.contains(r#"[label="Delta (d)"];"#));
+        assert!(!dot.contains("Echo"));
+        assert_eq!(dot.matches("->").count(), 3);
+    }
+
+    fn entry_lines(entries: &[VisibleEntry<&str>]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| {
+                let symbol = if entry.leaf {
+                    "  "
+                } else if entry.open {
+                    "▼ "
+                } else {
+                    "▶ "
+                };
+                format!("{}{symbol}{}", "  ".repeat(entry.depth), entry.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn visible_entries_match_render() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        state.close(&["b", "d"]);
+        state.open(vec!["h"]);
+        let entries = visible_entries(&items, &state, None);
+        let rendered = crate::Tree::new(&items)
+            .unwrap()
+            .render_to_string(&state, 30);
+        assert_eq!(entry_lines(&entries).join("\n"), rendered);
+        assert_eq!(entries.len(), 6);
+        assert!(entries[1].open);
+        assert!(!entries[3].open);
+        assert!(!entries[3].leaf);
+        assert!(entries[5].leaf);
+    }
+
+    #[test]
+    fn visible_entries_under() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        let entries = visible_entries(&items, &state, Some(&["b", "d"]));
+        let identifiers = entries
+            .iter()
+            .map(|entry| entry.identifier.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            identifiers,
+            [vec!["b", "d"], vec!["b", "d", "e"], vec!["b", "d", "f"]]
+        );
+        assert_eq!(entries[1].depth, 2);
+        assert_eq!(
+            entry_lines(&entries),
+            ["  ▼ Delta", "      Echo", "      Foxtrot"]
+        );
+
+        // Same as the matching rows of the rendered tree
+        let start = visible_entries(&items, &state, None)
+            .iter()
+            .position(|entry| entry.identifier == ["b", "d"])
+            .unwrap();
+        let rendered = crate::Tree::new(&items)
+            .unwrap()
+            .render_to_string(&state, 30);
+        let rendered = rendered
+            .lines()
+            .skip(start)
+            .take(entries.len())
+            .collect::<Vec<_>>();
+        assert_eq!(entry_lines(&entries), rendered);
+    }
+}
@@ -1,5 +1,8 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::ops::{Deref, Range};
+use std::rc::Rc;
 
+use crate::path_set::PathSet;
 use crate::tree_item::TreeItem;
 
 /// A flattened item of all visible [`TreeItem`]s.
@@ -7,57 +10,264 @@ use crate::tree_item::TreeItem;
 /// Generated via [`TreeState::flatten`](crate::TreeState::flatten).
 #[must_use]
 pub struct Flattened<'text, Identifier> {
-    pub identifier: Vec<Identifier>,
+    pub identifier: FlattenedIdentifier<Identifier>,
     pub item: &'text TreeItem<'text, Identifier>,
 }
 
 impl<Identifier> Flattened<'_, Identifier> {
     /// Zero based depth. Depth 0 means top level with 0 indentation.
     #[must_use]
-    pub fn depth(&self) -> usize {
-        self.identifier.len() - 1
+    pub const fn depth(&self) -> usize {
+        self.identifier.range.end - self.identifier.range.start - 1
+    }
+}
+
+/// Identifier of a [`Flattened`] item including the identifiers of its parents.
+///
+/// All items of one flatten share a buffer for their identifiers.
+/// An open item is followed by its first child which reuses the identifier of the item, so cloning and even very deeply nested items are cheap.
+/// Use it like a slice of identifiers, [`to_vec`](slice::to_vec) creates an owned copy.
+#[derive(Clone)]
+pub struct FlattenedIdentifier<Identifier> {
+    buffer: Rc<[Identifier]>,
+    range: Range<usize>,
+}
+
+impl<Identifier> Deref for FlattenedIdentifier<Identifier> {
+    type Target = [Identifier];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer[self.range.clone()]
+    }
+}
+
+impl<Identifier: core::fmt::Debug> core::fmt::Debug for FlattenedIdentifier<Identifier> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<Identifier: PartialEq> PartialEq for FlattenedIdentifier<Identifier> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<Identifier: Eq> Eq for FlattenedIdentifier<Identifier> {}
+
+impl<Identifier: PartialEq> PartialEq<[Identifier]> for FlattenedIdentifier<Identifier> {
+    fn eq(&self, other: &[Identifier]) -> bool {
+        **self == *other
+    }
+}
+
+impl<Identifier: PartialEq> PartialEq<Vec<Identifier>> for FlattenedIdentifier<Identifier> {
+    fn eq(&self, other: &Vec<Identifier>) -> bool {
+        **self == **other
+    }
+}
+
+impl<Identifier: PartialEq, const N: usize> PartialEq<[Identifier; N]>
+    for FlattenedIdentifier<Identifier>
+{
+    fn eq(&self, other: &[Identifier; N]) -> bool {
+        **self == *other
+    }
+}
+
+impl<Identifier: core::hash::Hash> core::hash::Hash for FlattenedIdentifier<Identifier> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
     }
 }
 
 /// Get a flat list of all visible [`TreeItem`]s.
 ///
-/// `current` starts empty: `&[]`
+/// `pages` contains the amount of shown pages of [paginated](TreeItem::paginated) items.
 #[must_use]
 pub fn flatten<'text, Identifier>(
-    open_identifiers: &HashSet<Vec<Identifier>>,
+    open_identifiers: &PathSet<Identifier>,
+    pages: &HashMap<Vec<Identifier>, usize>,
     items: &'text [TreeItem<'text, Identifier>],
-    current: &[Identifier],
 ) -> Vec<Flattened<'text, Identifier>>
 where
     Identifier: Clone + PartialEq + Eq + core::hash::Hash,
 {
-    let mut result = Vec::new();
-    for item in items {
-        let mut child_identifier = current.to_vec();
-        child_identifier.push(item.identifier.clone());
+    flatten_until(open_identifiers, pages, items, |_| false)
+}
 
-        let child_result = open_identifiers
-            .contains(&child_identifier)
-            .then(|| flatten(open_identifiers, &item.children, &child_identifier));
+/// Same as [`flatten`] but stops walking the items once `done` returns `true` for a row.
+pub fn flatten_until<'text, Identifier>(
+    open_identifiers: &PathSet<Identifier>,
+    pages: &HashMap<Vec<Identifier>, usize>,
+    items: &'text [TreeItem<'text, Identifier>],
+    done: impl FnMut(&TreeItem<Identifier>) -> bool,
+) -> Vec<Flattened<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    // Node of the parent in the set to look up its children without walking the whole identifier again.
+    // `Some(None)` is the top level, `None` when nothing below the parent is open.
+    flatten_walk(
+        Some(None),
+        |parent: Option<Option<usize>>, _identifier, item| {
+            let node = parent.and_then(|parent| open_identifiers.child(parent, &item.identifier));
+            let is_open = node.is_some_and(|node| open_identifiers.contains_node(node));
+            (is_open, node.map(Some))
+        },
+        pages,
+        items,
+        done,
+    )
+}
 
-        result.push(Flattened {
-            identifier: child_identifier,
-            item,
-        });
+/// Same as [`flatten`] but an item is open when `is_open` returns `true` for it.
+#[must_use]
+pub fn flatten_with<'text, Identifier, F>(
+    is_open: F,
+    pages: &HashMap<Vec<Identifier>, usize>,
+    items: &'text [TreeItem<'text, Identifier>],
+) -> Vec<Flattened<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+    F: Fn(&[Identifier], &TreeItem<Identifier>) -> bool,
+{
+    flatten_walk(
+        (),
+        |(), identifier, item| (is_open(identifier, item), ()),
+        pages,
+        items,
+        |_| false,
+    )
+}
+
+/// Walk the open items in order.
+///
+/// `is_open` gets the `Level` of the parent and returns whether the item is open together with the `Level` for its children.
+/// The walk stops once `done` returns `true` for a row.
+fn flatten_walk<'text, Identifier, Level, F>(
+    root: Level,
+    is_open: F,
+    pages: &HashMap<Vec<Identifier>, usize>,
+    items: &'text [TreeItem<'text, Identifier>],
+    mut done: impl FnMut(&TreeItem<Identifier>) -> bool,
+) -> Vec<Flattened<'text, Identifier>>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+    Level: Copy,
+    F: Fn(Level, &[Identifier], &TreeItem<Identifier>) -> (bool, Level),
+{
+    /// Children of an open item which still need to be flattened
+    struct Children<'text, Identifier, Level> {
+        items: std::slice::Iter<'text, TreeItem<'text, Identifier>>,
+        show_more: Option<&'text TreeItem<'text, Identifier>>,
+        /// Identifier of the open item in `buffer`
+        parent: Range<usize>,
+        level: Level,
+    }
+
+    /// Identifier of the child in the buffer.
+    /// It directly follows the parent when nothing was added after the parent yet, otherwise the parent is copied first.
+    fn push_child<Identifier: Clone>(
+        buffer: &mut Vec<Identifier>,
+        parent: &Range<usize>,
+        child: &Identifier,
+    ) -> Range<usize> {
+        let start = if parent.end == buffer.len() {
+            parent.start
+        } else {
+            let start = buffer.len();
+            buffer.extend_from_within(parent.clone());
+            start
+        };
+        buffer.push(child.clone());
+        start..buffer.len()
+    }
 
-        if let Some(mut child_result) = child_result {
-            result.append(&mut child_result);
+    // Explicit stack instead of recursion to not overflow the stack with very deep trees
+    let mut buffer = Vec::new();
+    let mut rows = Vec::new();
+    let mut stack = vec![Children {
+        items: items.iter(),
+        show_more: None,
+        parent: 0..0,
+        level: root,
+    }];
+    while let Some(children) = stack.last_mut() {
+        let Some(item) = children.items.next() else {
+            let Some(Children {
+                show_more: Some(more),
+                parent,
+                ..
+            }) = stack.pop()
+            else {
+                continue;
+            };
+            rows.push((push_child(&mut buffer, &parent, &more.identifier), more));
+            if done(more) {
+                break;
+            }
+            continue;
+        };
+
+        let range = push_child(&mut buffer, &children.parent, &item.identifier);
+        let identifier = &buffer[range.clone()];
+        let is_header = item.header && identifier.len() == 1;
+        let (is_open, level) = is_open(children.level, identifier, item);
+        if is_header || is_open {
+            let shown = shown_children(pages, identifier, item);
+            stack.push(Children {
+                items: item.children[..shown].iter(),
+                show_more: item
+                    .show_more
+                    .as_deref()
+                    .filter(|_| shown < item.children.len()),
+                parent: range.clone(),
+                level,
+            });
+        }
+        rows.push((range, item));
+        if done(item) {
+            break;
         }
     }
-    result
+
+    let buffer = Rc::<[Identifier]>::from(buffer);
+    rows.into_iter()
+        .map(|(range, item)| Flattened {
+            identifier: FlattenedIdentifier {
+                buffer: Rc::clone(&buffer),
+                range,
+            },
+            item,
+        })
+        .collect()
+}
+
+/// Amount of children shown of the [paginated](TreeItem::paginated) item.
+pub fn shown_children<Identifier>(
+    pages: &HashMap<Vec<Identifier>, usize>,
+    identifier: &[Identifier],
+    item: &TreeItem<Identifier>,
+) -> usize
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    if item.show_more.is_none() {
+        return item.children.len();
+    }
+    let pages = pages.get(identifier).copied().unwrap_or(1);
+    item.page_size
+        .saturating_mul(pages)
+        .min(item.children.len())
 }
 
 #[test]
 fn depth_works() {
-    let mut open = HashSet::new();
-    open.insert(vec!["b"]);
-    open.insert(vec!["b", "d"]);
-    let depths = flatten(&open, &TreeItem::example(), &[])
+    let mut open = PathSet::default();
+    open.insert(&["b"]);
+    open.insert(&["b", "d"]);
+    let depths = flatten(&open, &HashMap::new(), &TreeItem::example())
         .into_iter()
         .map(|flattened| flattened.depth())
         .collect::<Vec<_>>();
@@ -65,41 +275,60 @@ fn depth_works() {
 }
 
 #[cfg(test)]
-fn flatten_works(open: &HashSet<Vec<&'static str>>, expected: &[&str]) {
+fn flatten_works(open: &PathSet<&'static str>, expected: &[&str]) {
     let items = TreeItem::example();
-    let result = flatten(open, &items, &[]);
+    let result = flatten(open, &HashMap::new(), &items);
     let actual = result
         .into_iter()
-        .map(|flattened| flattened.identifier.into_iter().last().unwrap())
+        .map(|flattened| *flattened.identifier.last().unwrap())
         .collect::<Vec<_>>();
     assert_eq!(actual, expected);
 }
 
 #[test]
 fn flatten_nothing_open_is_top_level() {
-    let open = HashSet::new();
+    let open = PathSet::default();
     flatten_works(&open, &["a", "b", "h"]);
 }
 
 #[test]
 fn flatten_wrong_open_is_only_top_level() {
-    let mut open = HashSet::new();
-    open.insert(vec!["a"]);
-    open.insert(vec!["b", "d"]);
+    let mut open = PathSet::default();
+    open.insert(&["a"]);
+    open.insert(&["b", "d"]);
     flatten_works(&open, &["a", "b", "h"]);
 }
 
 #[test]
 fn flatten_one_is_open() {
-    let mut open = HashSet::new();
-    open.insert(vec!["b"]);
+    let mut open = PathSet::default();
+    open.insert(&["b"]);
     flatten_works(&open, &["a", "b", "c", "d", "g", "h"]);
 }
 
 #[test]
 fn flatten_all_open() {
-    let mut open = HashSet::new();
-    open.insert(vec!["b"]);
-    open.insert(vec!["b", "d"]);
+    let mut open = PathSet::default();
+    open.insert(&["b"]);
+    open.insert(&["b", "d"]);
     flatten_works(&open, &["a", "b", "c", "d", "e", "f", "g", "h"]);
 }
+
+#[test]
+fn flatten_until_stops_walking() {
+    let mut open = PathSet::default();
+    open.insert(&["b"]);
+    open.insert(&["b", "d"]);
+    let items = TreeItem::example();
+    let mut walked = Vec::new();
+    let result = flatten_until(&open, &HashMap::new(), &items, |item| {
+        walked.push(item.identifier);
+        item.identifier == "d"
+    });
+    assert_eq!(walked, ["a", "b", "c", "d"]);
+    let actual = result
+        .into_iter()
+        .map(|flattened| *flattened.identifier.last().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(actual, ["a", "b", "c", "d"]);
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+
+use crate::tree_item::TreeItem;
+
+/// Error of [`TreeItem::from_flat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlatTreeError<Identifier> {
+    /// The identifier is used by more than one record.
+    DuplicateIdentifier(Identifier),
+    /// The parent of the record does not exist.
+    MissingParent {
+        identifier: Identifier,
+        parent: Identifier,
+    },
+    /// The records reference each other as parents in a cycle.
+    Cycle(Vec<Identifier>),
+}
+
+impl<Identifier: Debug> Display for FlatTreeError<Identifier> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateIdentifier(identifier) => {
+                write!(f, "The identifier {identifier:?} exists multiple times")
+            }
+            Self::MissingParent { identifier, parent } => {
+                write!(f, "The parent {parent:?} of {identifier:?} does not exist")
+            }
+            Self::Cycle(identifiers) => {
+                write!(f, "The parents of {identifiers:?} form a cycle")
+            }
+        }
+    }
+}
+
+impl<Identifier: Debug> std::error::Error for FlatTreeError<Identifier> {}
+
+impl<Identifier> TreeItem<'static, Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Create [`TreeItem`]s from flat `(identifier, parent, text)` records like rows of a database table.
+    ///
+    /// Records without a parent are the top level items.
+    /// The records can be in any order, siblings keep the order in which they were given.
+    ///
+    /// # Errors
+    ///
+    /// Errors when an identifier exists multiple times, a parent does not exist or the parents form a cycle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::TreeItem;
+    /// let records = vec![
+    ///     (2, Some(1), "Child".to_owned()),
+    ///     (1, None, "Root".to_owned()),
+    /// ];
+    /// let items = TreeItem::from_flat(records)?;
+    /// assert_eq!(items.len(), 1);
+    /// assert_eq!(items[0].children().len(), 1);
+    /// # Ok::<(), tui_tree_widget::FlatTreeError<i32>>(())
+    /// ```
+    pub fn from_flat<I>(records: I) -> Result<Vec<Self>, FlatTreeError<Identifier>>
+    where
+        I: IntoIterator<Item = (Identifier, Option<Identifier>, String)>,
+    {
+        let records = records.into_iter().collect::<Vec<_>>();
+
+        let mut indices = HashMap::with_capacity(records.len());
+        for (index, (identifier, _, _)) in records.iter().enumerate() {
+            if indices.insert(identifier, index).is_some() {
+                return Err(FlatTreeError::DuplicateIdentifier(identifier.clone()));
+            }
+        }
+
+        let mut parents = vec![None; records.len()];
+        let mut children = vec![Vec::new(); records.len()];
+        let mut roots = Vec::new();
+        for (index, (identifier, parent, _)) in records.iter().enumerate() {
+            if let Some(parent) = parent {
+                let Some(&parent_index) = indices.get(parent) else {
+                    return Err(FlatTreeError::MissingParent {
+                        identifier: identifier.clone(),
+                        parent: parent.clone(),
+                    });
+                };
+                parents[index] = Some(parent_index);
+                children[parent_index].push(index);
+            } else {
+                roots.push(index);
+            }
+        }
+        drop(indices);
+
+        // Depth first order starting from the roots. Records not reached are part of a cycle or below one.
+        let mut order = Vec::with_capacity(records.len());
+        let mut stack = roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            stack.extend(children[index].iter().rev());
+        }
+        let mut reached = vec![false; records.len()];
+        for &index in &order {
+            reached[index] = true;
+        }
+        if let Some(start) = reached.iter().position(|reached| !reached) {
+            return Err(FlatTreeError::Cycle(find_cycle(&records, &parents, start)));
+        }
+
+        // Build the items bottom up so all children exist before their parent
+        let mut records = records.into_iter().map(Some).collect::<Vec<_>>();
+        let mut items = (0..records.len()).map(|_| None).collect::<Vec<_>>();
+        for &index in order.iter().rev() {
+            let Some((identifier, _, text)) = records[index].take() else {
+                continue;
+            };
+            let children = children[index]
+                .iter()
+                .filter_map(|&child| items[child].take())
+                .collect();
+            let mut item = Self::new_leaf(identifier, text);
+            item.children = children;
+            items[index] = Some(item);
+        }
+        Ok(roots
+            .into_iter()
+            .filter_map(|index| items[index].take())
+            .collect())
+    }
+}
+
+/// Follow the parents starting at `start` until one repeats and return the identifiers of that cycle.
+fn find_cycle<Identifier: Clone>(
+    records: &[(Identifier, Option<Identifier>, String)],
+    parents: &[Option<usize>],
+    start: usize,
+) -> Vec<Identifier> {
+    let mut path = Vec::<usize>::new();
+    let mut current = Some(start);
+    while let Some(index) = current {
+        if let Some(position) = path.iter().position(|&existing| existing == index) {
+            path.drain(..position);
+            break;
+        }
+        path.push(index);
+        current = parents[index];
+    }
+    path.into_iter()
+        .map(|index| records[index].0.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        identifier: &'static str,
+        parent: Option<&'static str>,
+    ) -> (&'static str, Option<&'static str>, String) {
+        (identifier, parent, identifier.to_uppercase())
+    }
+
+    fn identifiers(items: &[TreeItem<&'static str>]) -> Vec<&'static str> {
+        items.iter().map(|item| *item.identifier()).collect()
+    }
+
+    #[test]
+    fn out_of_order() {
+        let items = TreeItem::from_flat([
+            record("e", Some("d")),
+            record("c", Some("b")),
+            record("b", None),
+            record("f", Some("d")),
+            record("a", None),
+            record("d", Some("b")),
+        ])
+        .unwrap();
+        assert_eq!(identifiers(&items), ["b", "a"]);
+        assert_eq!(identifiers(items[0].children()), ["c", "d"]);
+        assert_eq!(identifiers(items[0].children()[1].children()), ["e", "f"]);
+        assert!(items[1].children().is_empty());
+        assert_eq!(items[0].text.lines[0].to_string(), "B");
+    }
+
+    #[test]
+    fn cycle() {
+        let error = TreeItem::from_flat([
+            record("root", None),
+            record("a", Some("c")),
+            record("b", Some("a")),
+            record("c", Some("b")),
+        ])
+        .unwrap_err();
+        let FlatTreeError::Cycle(mut identifiers) = error else {
+            panic!("expected cycle but got {error:?}");
+        };
+        identifiers.sort_unstable();
+        assert_eq!(identifiers, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn below_cycle_reports_only_cycle() {
+        let error = TreeItem::from_flat([
+            record("x", Some("a")),
+            record("a", Some("b")),
+            record("b", Some("a")),
+        ])
+        .unwrap_err();
+        assert_eq!(error, FlatTreeError::Cycle(vec!["a", "b"]));
+    }
+
+    #[test]
+    fn orphan() {
+        let error =
+            TreeItem::from_flat([record("a", None), record("b", Some("missing"))]).unwrap_err();
+        assert_eq!(
+            error,
+            FlatTreeError::MissingParent {
+                identifier: "b",
+                parent: "missing"
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate() {
+        let error = TreeItem::from_flat([record("a", None), record("a", None)]).unwrap_err();
+        assert_eq!(error, FlatTreeError::DuplicateIdentifier("a"));
+    }
+}
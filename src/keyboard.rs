@@ -0,0 +1,372 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::tree_state::TreeState;
+
+/// What happened when a [`KeyEvent`] was handled by [`TreeState::handle_key`].
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOutcome {
+    /// The key is not used by the tree.
+    /// The app can handle it on its own.
+    Ignored,
+    /// The key is part of a not yet completed key sequence like `g` or a count like `5`.
+    /// See [`TreeState::pending_keys`].
+    Pending,
+    /// The key was handled.
+    /// Contains `true` when the state changed.
+    Handled(bool),
+}
+
+/// Keyboard related part of the [`TreeState`].
+#[derive(Debug, Clone)]
+pub struct KeyState {
+    pending: String,
+    last_key: Option<Instant>,
+    timeout: Duration,
+}
+
+impl Default for KeyState {
+    fn default() -> Self {
+        Self {
+            pending: String::new(),
+            last_key: None,
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Where to scroll the selected item in the viewport
+#[derive(Clone, Copy)]
+enum ViewportPosition {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl<Identifier> TreeState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Keys of a not yet completed key sequence in [`handle_key`](Self::handle_key) like `g` or `5`.
+    ///
+    /// This can be shown in a status line.
+    #[must_use]
+    pub fn pending_keys(&self) -> &str {
+        &self.keys.pending
+    }
+
+    /// Set the time after which [pending keys](Self::pending_keys) are discarded.
+    ///
+    /// Defaults to 1 second.
+    pub const fn set_key_sequence_timeout(&mut self, timeout: Duration) {
+        self.keys.timeout = timeout;
+    }
+
+    /// Handles a crossterm [`KeyEvent`] with arrow keys and vim like keys.
+    ///
+    /// | Key | Action |
+    /// | --- | --- |
+    /// | `Up` / `k` | [`key_up`](Self::key_up) |
+    /// | `Down` / `j` | [`key_down`](Self::key_down) |
+    /// | `Left` / `h` | [`key_left`](Self::key_left) |
+    /// | `Right` / `l` | [`key_right`](Self::key_right) |
+    /// | `Enter` / `Space` | [`toggle_selected`](Self::toggle_selected) |
+    /// | `PageUp` / `PageDown` | [`key_page_up`](Self::key_page_up) / [`key_page_down`](Self::key_page_down) |
+    /// | `Home` / `gg` | [`select_first`](Self::select_first) |
+    /// | `End` / `G` | [`select_last`](Self::select_last) |
+    /// | `zt` / `zz` / `zb` | Scroll the selected item to the top / center / bottom of the view |
+    ///
+    /// A count can be given in front of `j`, `k` and `G` like `5j` to move 5 items down or `3G` to select the third item.
+    /// While a key sequence is not completed [`KeyOutcome::Pending`] is returned, see [`pending_keys`](Self::pending_keys).
+    /// Unknown keys discard the pending keys.
+    ///
+    /// Scrolling the selected item within the view is based on the last render.
+    pub fn handle_key(&mut self, event: KeyEvent) -> KeyOutcome {
+        self.handle_key_at(event, Instant::now())
+    }
+
+    /// Same as [`handle_key`](Self::handle_key) but with a given point in time of the event.
+    ///
+    /// This is helpful when the time of the event is known more precisely or for testing.
+    pub fn handle_key_at(&mut self, event: KeyEvent, now: Instant) -> KeyOutcome {
+        if event.kind == KeyEventKind::Release {
+            return KeyOutcome::Ignored;
+        }
+
+        let timed_out = self
+            .keys
+            .last_key
+            .is_some_and(|last| now.saturating_duration_since(last) > self.keys.timeout);
+        if timed_out {
+            self.keys.pending.clear();
+        }
+        self.keys.last_key = Some(now);
+
+        let modifiers = event.modifiers - KeyModifiers::SHIFT;
+        if !modifiers.is_empty() {
+            self.keys.pending.clear();
+            return KeyOutcome::Ignored;
+        }
+
+        let pending = std::mem::take(&mut self.keys.pending);
+        let (count, sequence) = pending.split_at(
+            pending
+                .find(|char: char| !char.is_ascii_digit())
+                .unwrap_or(pending.len()),
+        );
+        let count = count.parse::<usize>().ok();
+
+        let changed = match (sequence, event.code) {
+            ("", KeyCode::Char(char @ ('0'..='9' | 'g' | 'z')))
+                if char != '0' || count.is_some() =>
+            {
+                self.keys.pending = format!("{pending}{char}");
+                return KeyOutcome::Pending;
+            }
+
+            ("", KeyCode::Up | KeyCode::Char('k')) => self.repeat(count.unwrap_or(1), Self::key_up),
+            ("", KeyCode::Down | KeyCode::Char('j')) => {
+                self.repeat(count.unwrap_or(1), Self::key_down)
+            }
+            ("", KeyCode::Left | KeyCode::Char('h')) => self.key_left(),
+            ("", KeyCode::Right | KeyCode::Char('l')) => self.key_right(),
+            ("", KeyCode::Enter | KeyCode::Char(' ')) => self.toggle_selected(),
+            ("", KeyCode::PageUp) => self.key_page_up(),
+            ("", KeyCode::PageDown) => self.key_page_down(),
+            ("", KeyCode::Home) | ("g", KeyCode::Char('g')) => self.select_first(),
+            ("", KeyCode::End) => self.select_last(),
+            ("", KeyCode::Char('G')) => {
+                if let Some(count) = count {
+                    let index = count.saturating_sub(1).min(self.last_biggest_index);
+                    let identifier = self
+                        .last_identifiers
+                        .get(index)
+                        .map(|identifier| identifier.to_vec());
+                    self.select(identifier.unwrap_or_default())
+                } else {
+                    self.select_last()
+                }
+            }
+            ("z", KeyCode::Char('t')) => self.scroll_selected_to(ViewportPosition::Top),
+            ("z", KeyCode::Char('z')) => self.scroll_selected_to(ViewportPosition::Center),
+            ("z", KeyCode::Char('b')) => self.scroll_selected_to(ViewportPosition::Bottom),
+
+            ("", KeyCode::Esc) if !pending.is_empty() => false,
+            _ => return KeyOutcome::Ignored,
+        };
+        KeyOutcome::Handled(changed)
+    }
+
+    /// Run the step up to `count` times but stop once it changes nothing.
+    ///
+    /// More steps than items are never needed so huge counts do not block.
+    /// When [wrapping](Self::set_wrap) every amount of items steps ends up where it started so only the rest is walked.
+    fn repeat(&mut self, count: usize, step: fn(&mut Self) -> bool) -> bool {
+        let len = self.last_identifiers.len().max(1);
+        let count = if self.wrap {
+            count % len
+        } else {
+            count.min(len)
+        };
+        let mut changed = false;
+        for _ in 0..count {
+            if !step(self) {
+                break;
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Set the offset in a way the selected item is at the given position of the view of the last render.
+    ///
+    /// Returns `true` when the offset changed.
+    fn scroll_selected_to(&mut self, position: ViewportPosition) -> bool {
+        let Some(index) = self
+            .last_identifiers
+            .iter()
+            .position(|identifier| *identifier == self.selected)
+        else {
+            return false;
+        };
+
+        let available_height = usize::from(self.last_area.height);
+        let selected_height = self.last_heights.get(index).copied().unwrap_or(1);
+        let lines_above = match position {
+            ViewportPosition::Top => 0,
+            ViewportPosition::Center => available_height.saturating_sub(selected_height) / 2,
+            ViewportPosition::Bottom => available_height.saturating_sub(selected_height),
+        };
+
+        let mut offset = index;
+        let mut height = 0;
+        while offset > 0 {
+            let above = self.last_heights.get(offset - 1).copied().unwrap_or(1);
+            if height + above > lines_above {
+                break;
+            }
+            height += above;
+            offset -= 1;
+        }
+
+        self.ensure_selected_in_view_on_next_render = false;
+        let changed = self.offset != offset;
+        self.offset = offset;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidget;
+
+    use super::*;
+    use crate::{Tree, TreeItem};
+
+    fn items() -> Vec<TreeItem<'static, usize>> {
+        (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect()
+    }
+
+    fn render(items: &[TreeItem<'static, usize>], state: &mut TreeState<usize>) {
+        let area = Rect::new(0, 0, 10, 5);
+        let tree = Tree::new(items).unwrap();
+        StatefulWidget::render(tree, area, &mut Buffer::empty(area), state);
+    }
+
+    fn keys(state: &mut TreeState<usize>, keys: &str) -> KeyOutcome {
+        let now = Instant::now();
+        let mut outcome = KeyOutcome::Ignored;
+        for char in keys.chars() {
+            outcome = state.handle_key_at(KeyEvent::from(KeyCode::Char(char)), now);
+        }
+        outcome
+    }
+
+    #[test]
+    fn arrows_and_vim_keys() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        assert_eq!(
+            state.handle_key(KeyEvent::from(KeyCode::Down)),
+            KeyOutcome::Handled(true)
+        );
+        assert_eq!(state.selected(), [0]);
+        assert_eq!(keys(&mut state, "j"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [1]);
+        assert_eq!(keys(&mut state, "k"), KeyOutcome::Handled(true));
+        assert_eq!(keys(&mut state, "k"), KeyOutcome::Handled(false));
+        assert_eq!(state.selected(), [0]);
+    }
+
+    #[test]
+    fn first_and_last() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        assert_eq!(keys(&mut state, "G"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [19]);
+        assert_eq!(keys(&mut state, "g"), KeyOutcome::Pending);
+        assert_eq!(state.pending_keys(), "g");
+        assert_eq!(keys(&mut state, "g"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [0]);
+        assert_eq!(state.pending_keys(), "");
+        assert_eq!(keys(&mut state, "3G"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [2]);
+    }
+
+    #[test]
+    fn count_prefix() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        assert_eq!(keys(&mut state, "1"), KeyOutcome::Pending);
+        assert_eq!(keys(&mut state, "2"), KeyOutcome::Pending);
+        assert_eq!(state.pending_keys(), "12");
+        assert_eq!(keys(&mut state, "j"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [11]);
+        render(&items, &mut state);
+        assert_eq!(keys(&mut state, "10k"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [1]);
+        render(&items, &mut state);
+        assert_eq!(state.get_offset(), 1);
+    }
+
+    #[test]
+    fn huge_count_is_clamped() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        assert_eq!(keys(&mut state, "99999999999j"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [19]);
+        state.set_wrap(true);
+        // 99999999999 = 19 mod 20
+        assert_eq!(keys(&mut state, "99999999999k"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [0]);
+        assert_eq!(keys(&mut state, "3k"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [17]);
+        assert_eq!(keys(&mut state, "40j"), KeyOutcome::Handled(false));
+        assert_eq!(state.selected(), [17]);
+    }
+
+    #[test]
+    fn unknown_key_discards_pending() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        assert_eq!(keys(&mut state, "5x"), KeyOutcome::Ignored);
+        assert_eq!(state.pending_keys(), "");
+        assert_eq!(keys(&mut state, "j"), KeyOutcome::Handled(true));
+        assert_eq!(state.selected(), [0]);
+    }
+
+    #[test]
+    fn pending_times_out() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        let g = KeyEvent::from(KeyCode::Char('g'));
+        assert_eq!(state.handle_key_at(g, now), KeyOutcome::Pending);
+        let later = now + Duration::from_secs(2);
+        assert_eq!(state.handle_key_at(g, later), KeyOutcome::Pending);
+        assert!(state.selected().is_empty());
+    }
+
+    #[test]
+    fn modifiers_are_ignored() {
+        let mut state = TreeState::<usize>::default();
+        let event = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(state.handle_key(event), KeyOutcome::Ignored);
+    }
+
+    #[test]
+    fn scroll_selected_in_view() {
+        let items = items();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        state.select(vec![10]);
+        render(&items, &mut state);
+        assert_eq!(state.get_offset(), 6);
+
+        assert_eq!(keys(&mut state, "zt"), KeyOutcome::Handled(true));
+        assert_eq!(state.get_offset(), 10);
+        render(&items, &mut state);
+        assert_eq!(state.get_offset(), 10);
+
+        assert_eq!(keys(&mut state, "zz"), KeyOutcome::Handled(true));
+        assert_eq!(state.get_offset(), 8);
+        render(&items, &mut state);
+        assert_eq!(state.get_offset(), 8);
+
+        assert_eq!(keys(&mut state, "zb"), KeyOutcome::Handled(true));
+        assert_eq!(state.get_offset(), 6);
+        assert_eq!(keys(&mut state, "zb"), KeyOutcome::Handled(false));
+    }
+}
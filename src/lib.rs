@@ -6,20 +6,48 @@ The user interaction state (like the current selection) is stored in the [`TreeS
 */
 
 use std::collections::HashSet;
+use std::rc::Rc;
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Scrollbar, ScrollbarState, StatefulWidget, Widget};
-use unicode_width::UnicodeWidthStr;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Scrollbar, ScrollbarState, StatefulWidget, Widget, WidgetRef};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-pub use crate::flatten::Flattened;
+pub use crate::breadcrumb::breadcrumb;
+pub use crate::event::{TreeEffect, TreeEvent};
+pub use crate::flatten::{Flattened, FlattenedIdentifier};
+pub use crate::from_flat::FlatTreeError;
+#[cfg(feature = "crossterm")]
+pub use crate::keyboard::KeyOutcome;
+#[cfg(feature = "crossterm")]
+pub use crate::mouse::MouseOutcome;
+pub use crate::overview::{overview, OverviewBucket, OverviewClass};
+pub use crate::path_set::PathSet;
+pub use crate::state_op::{ApplyReport, StateOp};
+pub use crate::tree_data::TreeData;
 pub use crate::tree_item::TreeItem;
-pub use crate::tree_state::TreeState;
+pub use crate::tree_state::{Enter, EnterBehavior, ToggleOutcome, TooDeep, TreeState};
 
+mod breadcrumb;
+mod event;
+pub mod export;
 mod flatten;
+mod from_flat;
+#[cfg(feature = "crossterm")]
+mod keyboard;
+#[cfg(feature = "crossterm")]
+mod mouse;
+mod overview;
+mod path_set;
+mod state_op;
+mod tree_data;
 mod tree_item;
 mod tree_state;
+mod undo;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 /// A `Tree` which can be rendered.
 ///
@@ -62,8 +90,14 @@ pub struct Tree<'a, Identifier> {
 
     /// Style used to render selected item
     highlight_style: Style,
+    /// Style used to render [headers](TreeItem::new_header)
+    header_style: Style,
     /// Symbol in front of the selected item (Shift all items to the right)
     highlight_symbol: &'a str,
+    /// Whether the widget has the focus. Otherwise the selection is rendered with `unfocused_highlight_style` and without `highlight_symbol`
+    focused: bool,
+    /// Style used to render the selected item when not focused. `None` uses `highlight_style`.
+    unfocused_highlight_style: Option<Style>,
 
     /// Symbol displayed in front of a closed node (As in the children are currently not visible)
     node_closed_symbol: &'a str,
@@ -71,6 +105,104 @@ pub struct Tree<'a, Identifier> {
     node_open_symbol: &'a str,
     /// Symbol displayed in front of a node without children.
     node_no_children_symbol: &'a str,
+    node_empty_branch_symbol: &'a str,
+
+    /// Displayed as the child of an opened lazy node which is marked as loading
+    loading_placeholder: Line<'a>,
+    /// Displayed as the child of a node at the max path depth of the state
+    depth_limit_marker: Line<'a>,
+
+    /// How to scroll the selected item into view
+    scroll_mode: ScrollMode,
+    /// How to scroll the children of a just opened item into view
+    reveal_on_open: RevealOnOpen,
+
+    /// Style of the line numbers in front of each item. `None` when disabled.
+    line_numbers: Option<Style>,
+    /// Creates the line number label from the index in the visible items
+    line_number_fn: Callback<LineNumberFn<'a, Identifier>>,
+
+    /// Style of the description of the selected item. `None` when descriptions are not shown.
+    selected_description: Option<Style>,
+
+    /// Style of the bookmarks section header. `None` when bookmarks are not shown.
+    bookmarks: Option<Style>,
+
+    /// Text shown instead of the item being edited
+    edit_text: &'a str,
+    /// Position of the cursor in the `edit_text` in chars
+    edit_cursor: usize,
+    /// Style of the item being edited
+    edit_style: Style,
+
+    /// Paints additional cells after each row was rendered
+    row_overlay: Option<Callback<RowOverlayFn<'a, Identifier>>>,
+
+    /// Items for which this returns `true` are not shown
+    hide_fn: Option<Callback<HideFn<'a, Identifier>>>,
+
+    /// Separator between the items of a chain merged into a single row. `None` when chains are not merged.
+    compact_separator: Option<&'a str>,
+}
+
+/// How the [`Tree`] scrolls to keep the selected [`TreeItem`] in view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollMode {
+    /// Scroll only as much as needed so the selected item is at the top or bottom edge of the view.
+    #[default]
+    Minimal,
+    /// Scroll the selected item into the vertical center of the view.
+    ///
+    /// Near the start and end of the tree the offset is clamped so there is no empty space.
+    Center,
+}
+
+/// How the [`Tree`] scrolls when a [`TreeItem`] was opened but its children are below the view.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RevealOnOpen {
+    /// Do not scroll.
+    #[default]
+    None,
+    /// Scroll so the first child of the opened item is visible.
+    FirstChild,
+    /// Scroll so as many of the visible descendants fit while the opened item stays visible.
+    AsManyAsFit,
+}
+
+/// A rendered row of a [`TreeItem`] given to the [`row_overlay`](Tree::row_overlay) of the [`Tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowContext<'a, Identifier> {
+    /// Area of the row including the highlight symbol, indentation and line number
+    pub area: Rect,
+    /// Identifier of the item including the identifiers of its parents
+    pub identifier: &'a [Identifier],
+    /// Zero based depth. Depth 0 means top level with 0 indentation.
+    pub depth: usize,
+    /// The item is selected
+    pub selected: bool,
+    /// The item is open
+    pub open: bool,
+    /// The item has no children and is not [lazy](TreeItem::new_lazy)
+    pub leaf: bool,
+}
+
+type LineNumberFn<'a, Identifier> = dyn Fn(usize, &[Identifier]) -> String + 'a;
+type RowOverlayFn<'a, Identifier> = dyn Fn(&RowContext<Identifier>, &mut Buffer) + 'a;
+type HideFn<'a, Identifier> = dyn Fn(&[Identifier], &TreeItem<Identifier>) -> bool + 'a;
+
+/// Function used by the [`Tree`] which can be cloned and debug printed
+struct Callback<F: ?Sized>(Rc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> core::fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Callback")
+    }
 }
 
 impl<'a, Identifier> Tree<'a, Identifier>
@@ -100,10 +232,28 @@ where
             scrollbar: None,
             style: Style::new(),
             highlight_style: Style::new(),
+            header_style: Style::new(),
             highlight_symbol: "",
+            focused: true,
+            unfocused_highlight_style: None,
             node_closed_symbol: "\u{25b6} ", // Arrow to right
             node_open_symbol: "\u{25bc} ",   // Arrow down
             node_no_children_symbol: "  ",
+            node_empty_branch_symbol: "\u{25b6} ", // Arrow to right
+            loading_placeholder: Line::from("Loading…"),
+            depth_limit_marker: Line::from("… depth limit"),
+            scroll_mode: ScrollMode::Minimal,
+            reveal_on_open: RevealOnOpen::None,
+            line_numbers: None,
+            line_number_fn: Callback(Rc::new(|index, _| (index + 1).to_string())),
+            selected_description: None,
+            bookmarks: None,
+            edit_text: "",
+            edit_cursor: 0,
+            edit_style: Style::new(),
+            row_overlay: None,
+            hide_fn: None,
+            compact_separator: None,
         })
     }
 
@@ -133,11 +283,45 @@ where
         self
     }
 
+    /// Style of [headers](TreeItem::new_header).
+    pub const fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
     pub const fn highlight_symbol(mut self, highlight_symbol: &'a str) -> Self {
         self.highlight_symbol = highlight_symbol;
         self
     }
 
+    /// Whether the widget has the focus, like the active pane of an app with multiple panes.
+    ///
+    /// When not focused the selected item is rendered with the [`unfocused_highlight_style`](Self::unfocused_highlight_style) and without the [`highlight_symbol`](Self::highlight_symbol).
+    ///
+    /// Defaults to `true`.
+    pub const fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Style used to render the selected item when not [focused](Self::focused).
+    ///
+    /// Defaults to the [`highlight_style`](Self::highlight_style).
+    pub const fn unfocused_highlight_style(mut self, style: Style) -> Self {
+        self.unfocused_highlight_style = Some(style);
+        self
+    }
+
+    /// Style of the selected item depending on the [focus](Self::focused)
+    fn selected_style(&self) -> Style {
+        if self.focused {
+            self.highlight_style
+        } else {
+            self.unfocused_highlight_style
+                .unwrap_or(self.highlight_style)
+        }
+    }
+
     pub const fn node_closed_symbol(mut self, symbol: &'a str) -> Self {
         self.node_closed_symbol = symbol;
         self
@@ -152,6 +336,551 @@ where
         self.node_no_children_symbol = symbol;
         self
     }
+
+    /// Symbol of [expandable](TreeItem::expandable) items without children.
+    ///
+    /// Defaults to the same symbol as closed nodes.
+    pub const fn empty_branch_symbol(mut self, symbol: &'a str) -> Self {
+        self.node_empty_branch_symbol = symbol;
+        self
+    }
+
+    /// Line shown as the only child of an opened [lazy](TreeItem::new_lazy) node while it is [loading](TreeState::mark_loading).
+    ///
+    /// Defaults to `Loading…`.
+    pub fn loading_placeholder<T>(mut self, placeholder: T) -> Self
+    where
+        T: Into<Line<'a>>,
+    {
+        self.loading_placeholder = placeholder.into();
+        self
+    }
+
+    /// Line shown as the only child of nodes with children which can not be opened because of [`TreeState::set_max_path_depth`].
+    ///
+    /// Defaults to `… depth limit`.
+    pub fn depth_limit_marker<T>(mut self, marker: T) -> Self
+    where
+        T: Into<Line<'a>>,
+    {
+        self.depth_limit_marker = marker.into();
+        self
+    }
+
+    /// How to scroll when the selection changes.
+    ///
+    /// Defaults to [`ScrollMode::Minimal`].
+    /// Also see [`TreeState::center_selected`] to center the selection once.
+    pub const fn scroll_mode(mut self, scroll_mode: ScrollMode) -> Self {
+        self.scroll_mode = scroll_mode;
+        self
+    }
+
+    /// How to scroll when an item was opened since the last render and its children would be below the view.
+    ///
+    /// Defaults to [`RevealOnOpen::None`].
+    pub const fn reveal_on_open(mut self, reveal_on_open: RevealOnOpen) -> Self {
+        self.reveal_on_open = reveal_on_open;
+        self
+    }
+
+    /// Show line numbers right aligned in front of each item, like in a text editor.
+    ///
+    /// The line number is the position in all currently viewable (including by scrolling) [`TreeItem`]s starting with 1.
+    /// So opening or closing a node changes the line numbers of the items after it.
+    /// The width of the line numbers is based on the widest line number currently on screen.
+    ///
+    /// Use [`line_number_fn`](Self::line_number_fn) to show something else.
+    pub const fn line_numbers(mut self, enabled: bool, style: Style) -> Self {
+        self.line_numbers = if enabled { Some(style) } else { None };
+        self
+    }
+
+    /// Create the label shown by [`line_numbers`](Self::line_numbers) with a custom function.
+    ///
+    /// The function gets the zero based index in all currently viewable [`TreeItem`]s and the identifier of the item.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ratatui::style::Style;
+    /// # use tui_tree_widget::{Tree, TreeItem};
+    /// # let items = vec![TreeItem::new_leaf("l", "leaf")];
+    /// let tree = Tree::new(&items)?
+    ///     .line_numbers(true, Style::new())
+    ///     .line_number_fn(|index, _identifier| format!("#{index}"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn line_number_fn<F>(mut self, line_number_fn: F) -> Self
+    where
+        F: Fn(usize, &[Identifier]) -> String + 'a,
+    {
+        self.line_number_fn = Callback(Rc::new(line_number_fn));
+        self
+    }
+
+    /// Paint additional cells onto each row after it was rendered, like a progress bar or a status glyph.
+    ///
+    /// The function gets a [`RowContext`] and a [`Buffer`] containing only the [area](RowContext::area) of the row.
+    /// Writing outside of that area panics like with any other [`Buffer`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{Tree, TreeItem};
+    /// # let items = vec![TreeItem::new_leaf("l", "leaf")];
+    /// let tree = Tree::new(&items)?.row_overlay(|row, buf| {
+    ///     if row.selected {
+    ///         buf.set_string(row.area.right() - 1, row.area.y, "*", ratatui::style::Style::new());
+    ///     }
+    /// });
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn row_overlay<F>(mut self, row_overlay: F) -> Self
+    where
+        F: Fn(&RowContext<Identifier>, &mut Buffer) + 'a,
+    {
+        self.row_overlay = Some(Callback(Rc::new(row_overlay)));
+        self
+    }
+
+    /// Show the [description](TreeItem::description) of the selected [`TreeItem`] on an extra line below it.
+    ///
+    /// Descriptions of items which are not selected are never shown.
+    pub const fn show_selected_description(mut self, enabled: bool, style: Style) -> Self {
+        self.selected_description = if enabled { Some(style) } else { None };
+        self
+    }
+
+    /// Show the [bookmarked](TreeState::toggle_bookmark_selected) [`TreeItem`]s in a section above the tree.
+    ///
+    /// The section starts with a `Bookmarks` header in the given style followed by the first line of the text of each bookmarked item.
+    /// Bookmarks which no longer exist in the items are skipped.
+    /// The rows can be selected like the items of the tree and [`TreeState::enter`] jumps to the bookmarked item.
+    pub const fn show_bookmarks(mut self, enabled: bool, style: Style) -> Self {
+        self.bookmarks = if enabled { Some(style) } else { None };
+        self
+    }
+
+    /// Text shown instead of the text of the [`TreeItem`] which is [edited](TreeState::begin_edit).
+    ///
+    /// The indentation and node symbols stay the same while editing.
+    pub const fn edit_text(mut self, text: &'a str) -> Self {
+        self.edit_text = text;
+        self
+    }
+
+    /// Position of the cursor in the [`edit_text`](Self::edit_text) in chars.
+    ///
+    /// The cell of the cursor is shown reversed.
+    /// A cursor after the last char is shown on the cell after the text.
+    pub const fn edit_cursor(mut self, cursor: usize) -> Self {
+        self.edit_cursor = cursor;
+        self
+    }
+
+    /// Style of the [`edit_text`](Self::edit_text).
+    pub const fn edit_style(mut self, style: Style) -> Self {
+        self.edit_style = style;
+        self
+    }
+
+    /// Render all currently viewable (including by scrolling) [`TreeItem`]s into a `String` without styling.
+    ///
+    /// The result contains the lines the widget would draw with the given `width` (without the [`Block`] and scrollbar), separated by `\n`.
+    /// Trailing whitespace of each line is removed.
+    /// This is helpful for snapshot tests or copying the tree to the clipboard.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{Tree, TreeItem, TreeState};
+    /// let items = vec![TreeItem::new("r", "Root", vec![TreeItem::new_leaf("l", "Leaf")])?];
+    /// let mut state = TreeState::default();
+    /// state.open(vec!["r"]);
+    ///
+    /// let text = Tree::new(&items)?.render_to_string(&state, 20);
+    /// assert_eq!(text, "▼ Root\n    Leaf");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[must_use]
+    pub fn render_to_string(&self, state: &TreeState<Identifier>, width: usize) -> String {
+        let mut state = state.clone();
+        state.ensure_selected_in_view_on_next_render = false;
+        let heights = self
+            .flatten(&state)
+            .0
+            .iter()
+            .map(|flattened| self.row_height(flattened, &state))
+            .collect::<Vec<_>>();
+
+        let tree = Self {
+            block: None,
+            scrollbar: None,
+            ..self.clone()
+        };
+
+        // A Buffer can only contain u16::MAX cells so render in multiple pages when needed
+        let width = u16::try_from(width).unwrap_or(u16::MAX);
+        let page_height = (u16::MAX / width.max(1))
+            .min(u16::try_from(heights.iter().sum::<usize>()).unwrap_or(u16::MAX));
+        let area = Rect::new(0, 0, width, page_height);
+
+        let mut lines = Vec::new();
+        let mut index = 0;
+        while index < heights.len() && !area.is_empty() {
+            state.offset = index;
+            let mut buffer = Buffer::empty(area);
+            StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+            let mut rendered = state.last_rendered_identifiers.len();
+            if rendered == 0 {
+                break;
+            }
+            let mut line_count = heights[index..index + rendered].iter().sum::<usize>();
+            if line_count > usize::from(page_height) {
+                // The last item is cut off, render it on the next page instead
+                if rendered > 1 {
+                    rendered -= 1;
+                    line_count -= heights[index + rendered];
+                } else {
+                    line_count = usize::from(page_height);
+                }
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            for y in 0..line_count as u16 {
+                let mut line = String::new();
+                let mut skip = 0;
+                for x in area.left()..area.right() {
+                    let symbol = buffer.get(x, y).symbol();
+                    if skip > 0 {
+                        // Cells hidden by a previous wide symbol
+                        skip -= 1;
+                        continue;
+                    }
+                    skip = symbol.width().saturating_sub(1);
+                    line += symbol;
+                }
+                line.truncate(line.trim_end().len());
+                lines.push(line);
+            }
+            index += rendered;
+        }
+        lines.join("\n")
+    }
+
+    /// Render the bookmarks section at the top of the area and return the area remaining for the tree
+    fn render_bookmarks(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        state: &mut TreeState<Identifier>,
+    ) -> Rect {
+        let bookmarks = self.bookmarks.map_or_else(Vec::new, |_| {
+            state
+                .bookmarks
+                .iter()
+                .filter_map(|bookmark| {
+                    TreeItem::find(self.items, bookmark).map(|item| (bookmark.clone(), item))
+                })
+                .collect::<Vec<_>>()
+        });
+        state.last_bookmarks = bookmarks
+            .iter()
+            .map(|(bookmark, _)| bookmark.clone())
+            .collect();
+        let Some(header_style) = self.bookmarks.filter(|_| !bookmarks.is_empty()) else {
+            return area;
+        };
+
+        let width = usize::from(area.width);
+        let mut rows = area.rows();
+        if let Some(row) = rows.next() {
+            buf.set_stringn(row.x, row.y, "Bookmarks", width, header_style);
+        }
+        let has_selection = !state.selected.is_empty() || state.selected_bookmark.is_some();
+        let blank_symbol = " ".repeat(self.highlight_symbol.width());
+        for ((bookmark, item), row) in bookmarks.iter().zip(rows) {
+            let is_selected = state.selected_bookmark.as_ref() == Some(bookmark);
+            let symbol = if is_selected && self.focused {
+                self.highlight_symbol
+            } else if has_selection {
+                &blank_symbol
+            } else {
+                ""
+            };
+            let item_style = item.text.style;
+            let (x, _) = buf.set_stringn(row.x, row.y, symbol, width, item_style);
+            let (x, _) = buf.set_stringn(
+                x,
+                row.y,
+                self.node_no_children_symbol,
+                usize::from(row.right() - x),
+                item_style,
+            );
+            if let Some(line) = item.text.lines.first() {
+                let text_area = Rect {
+                    x,
+                    width: row.right() - x,
+                    ..row
+                };
+                line.clone().patch_style(item_style).render(text_area, buf);
+            }
+            if is_selected {
+                buf.set_style(row, self.selected_style());
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let section_height = (bookmarks.len() as u16 + 1).min(area.height);
+        Rect {
+            y: area.y + section_height,
+            height: area.height - section_height,
+            ..area
+        }
+    }
+
+    /// Hide [`TreeItem`]s without removing them, like hidden files in a file browser.
+    ///
+    /// The function gets the identifier of the item including the identifiers of its parents and the item itself.
+    /// When it returns `true` the item and its descendants are neither rendered nor reachable by navigation.
+    /// When the selected item is hidden it stays selected and the next navigation like [`key_down`](TreeState::key_down) selects the closest visible item before it instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{Tree, TreeItem};
+    /// # let items = vec![TreeItem::new_leaf(".git", ".git")];
+    /// let show_hidden = false;
+    /// let tree = Tree::new(&items)?
+    ///     .hide_fn(move |identifier, _item| !show_hidden && identifier.last().is_some_and(|name| name.starts_with('.')));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn hide_fn<F>(mut self, hide_fn: F) -> Self
+    where
+        F: Fn(&[Identifier], &TreeItem<Identifier>) -> bool + 'a,
+    {
+        self.hide_fn = Some(Callback(Rc::new(hide_fn)));
+        self
+    }
+
+    /// Merge chains of items having exactly one child with children into a single row like `com / example / app`.
+    ///
+    /// The row acts as the deepest item of the chain: selecting, opening and closing it changes that item.
+    /// The other items of the chain are shown as if they were open.
+    /// A chain ends at an item with multiple children or with a single child without children.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{Tree, TreeItem, TreeState};
+    /// let items = vec![TreeItem::new("com", "com", vec![
+    ///     TreeItem::new("example", "example", vec![TreeItem::new_leaf("app", "app")])?,
+    /// ])?];
+    /// let mut state = TreeState::default();
+    /// state.open(vec!["com", "example"]);
+    ///
+    /// let text = Tree::new(&items)?
+    ///     .compact_single_child(true, " / ")
+    ///     .render_to_string(&state, 20);
+    /// assert_eq!(text, "▼ com / example\n    app");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub const fn compact_single_child(mut self, compact: bool, separator: &'a str) -> Self {
+        self.compact_separator = if compact { Some(separator) } else { None };
+        self
+    }
+
+    /// All currently viewable [`TreeItem`]s without the [hidden](Self::hide_fn) ones.
+    ///
+    /// Items merged into their only child by [`compact_single_child`](Self::compact_single_child) are not included.
+    /// When the selected item is hidden the index of the closest visible item before it, otherwise after it, is returned too.
+    fn flatten(
+        &self,
+        state: &TreeState<Identifier>,
+    ) -> (Vec<Flattened<'a, Identifier>>, Option<usize>) {
+        let mut visible = if self.compact_separator.is_some() {
+            flatten::flatten_with(
+                |identifier, item| {
+                    state.opened.contains(identifier) || is_chain_link(identifier, item)
+                },
+                &state.pages,
+                self.items,
+            )
+        } else {
+            state.flatten(self.items)
+        };
+        // Amount of visible items before the hidden selected one
+        let mut visible_before_selected = None;
+        if let Some(hide_fn) = &self.hide_fn {
+            let mut hidden: Option<FlattenedIdentifier<Identifier>> = None;
+            let mut kept = 0;
+            visible.retain(|flattened| {
+                let below_hidden = hidden
+                    .as_ref()
+                    .is_some_and(|hidden| flattened.identifier.starts_with(hidden));
+                let hide = below_hidden || (hide_fn.0)(&flattened.identifier, flattened.item);
+                if hide && !below_hidden {
+                    hidden = Some(flattened.identifier.clone());
+                }
+                if hide && flattened.identifier == state.selected {
+                    visible_before_selected = Some(kept);
+                }
+                kept += usize::from(!hide);
+                !hide
+            });
+        }
+        if self.compact_separator.is_some() {
+            let mut remaining = std::mem::take(&mut visible).into_iter().peekable();
+            let mut index = 0;
+            while let Some(flattened) = remaining.next() {
+                let merged = is_chain_link(&flattened.identifier, flattened.item)
+                    && remaining
+                        .peek()
+                        .is_some_and(|next| next.identifier.starts_with(&flattened.identifier));
+                if merged {
+                    if let Some(before) = visible_before_selected
+                        .as_mut()
+                        .filter(|before| index < **before)
+                    {
+                        *before -= 1;
+                    }
+                } else {
+                    visible.push(flattened);
+                }
+                index += 1;
+            }
+        }
+        let closest = visible_before_selected
+            .filter(|_| !visible.is_empty())
+            .map(|before| before.saturating_sub(1));
+        (visible, closest)
+    }
+
+    /// Depth and text of each visible row when chains are merged by [`compact_single_child`](Self::compact_single_child).
+    ///
+    /// Empty when chains are not merged.
+    fn compact_rows(&self, visible: &[Flattened<'a, Identifier>]) -> Vec<CompactRow<'a>> {
+        let Some(separator) = self.compact_separator else {
+            return Vec::new();
+        };
+        let mut rows = Vec::with_capacity(visible.len());
+        // Visible parents of the current row together with their depth
+        let mut parents: Vec<(&[Identifier], &TreeItem<Identifier>, usize)> = Vec::new();
+        for flattened in visible {
+            let identifier = &*flattened.identifier;
+            while parents
+                .last()
+                .is_some_and(|(parent, ..)| !identifier.starts_with(parent))
+            {
+                parents.pop();
+            }
+            let (start, depth, siblings) = parents
+                .last()
+                .map_or((0, 0, self.items), |(parent, item, depth)| {
+                    (parent.len(), depth + 1, item.children.as_slice())
+                });
+
+            // The items between the visible parent and this row were merged into it
+            let mut chain = Vec::new();
+            let mut current = siblings
+                .iter()
+                .find(|item| item.identifier == identifier[start]);
+            for _ in start + 1..identifier.len() {
+                let Some(item) = current else {
+                    break;
+                };
+                chain.push(item);
+                current = item.children.first();
+            }
+            let text = (!chain.is_empty()).then(|| merged_text(&chain, flattened.item, separator));
+            rows.push(CompactRow { depth, text });
+            parents.push((identifier, flattened.item, depth));
+        }
+        rows
+    }
+
+    /// Height of the item including its description and the placeholder of loading items
+    fn row_height(
+        &self,
+        flattened: &Flattened<Identifier>,
+        state: &TreeState<Identifier>,
+    ) -> usize {
+        flattened.item.height()
+            + usize::from(self.shows_description(flattened, state))
+            + usize::from(state.shows_loading_placeholder(flattened))
+            + usize::from(state.shows_depth_limit(flattened))
+    }
+
+    /// Whether the description is shown below the item
+    fn shows_description(
+        &self,
+        flattened: &Flattened<Identifier>,
+        state: &TreeState<Identifier>,
+    ) -> bool {
+        self.selected_description.is_some()
+            && flattened.item.description.is_some()
+            && flattened.identifier == state.selected
+    }
+
+    /// Amount of hidden children when the identifier is the show more row of a [paginated](TreeItem::paginated) item
+    fn hidden_by_show_more(
+        &self,
+        identifier: &[Identifier],
+        state: &TreeState<Identifier>,
+    ) -> Option<usize> {
+        let parent = TreeItem::find_show_more_parent(self.items, identifier)?;
+        let parent_identifier = &identifier[..identifier.len() - 1];
+        let shown = flatten::shown_children(&state.pages, parent_identifier, parent);
+        Some(parent.children.len() - shown)
+    }
+
+    /// Render the [`edit_text`](Self::edit_text) with its cursor into the first line of the area.
+    ///
+    /// When the cursor would be outside of the area the text is shifted to the left.
+    #[allow(clippy::cast_possible_truncation)]
+    fn render_edit(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let area = Rect { height: 1, ..area };
+        buf.set_style(area, self.edit_style);
+
+        let width = usize::from(area.width);
+        let chars = self.edit_text.chars().collect::<Vec<_>>();
+        let cursor = self.edit_cursor.min(chars.len());
+        let cursor_width = chars
+            .get(cursor)
+            .map_or(1, |char| char.width().unwrap_or(0).max(1));
+        let mut skip = 0;
+        while skip < cursor
+            && chars[skip..cursor]
+                .iter()
+                .map(|char| char.width().unwrap_or(0))
+                .sum::<usize>()
+                + cursor_width
+                > width
+        {
+            skip += 1;
+        }
+
+        let visible = chars[skip..].iter().collect::<String>();
+        buf.set_stringn(area.x, area.y, visible, width, self.edit_style);
+
+        let before_cursor = chars[skip..cursor]
+            .iter()
+            .map(|char| char.width().unwrap_or(0))
+            .sum::<usize>();
+        let cursor_x = area.x + before_cursor.min(width - 1) as u16;
+        let cursor_area = Rect {
+            x: cursor_x,
+            width: (cursor_width as u16).min(area.right() - cursor_x),
+            ..area
+        };
+        buf.set_style(
+            cursor_area,
+            self.edit_style.add_modifier(Modifier::REVERSED),
+        );
+    }
 }
 
 #[test]
@@ -169,30 +898,91 @@ where
 {
     type State = TreeState<Identifier>;
 
+    fn render(self, full_area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        StatefulWidget::render(&self, full_area, buf, state);
+    }
+}
+
+/// Render the same `Tree` multiple times without creating it again.
+impl<Identifier> StatefulWidget for &Tree<'_, Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    type State = TreeState<Identifier>;
+
     #[allow(clippy::too_many_lines)]
     fn render(self, full_area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         buf.set_style(full_area, self.style);
 
         // Get the inner area inside a possible block, otherwise use the full area
-        let area = self.block.map_or(full_area, |block| {
+        let area = self.block.as_ref().map_or(full_area, |block| {
             let inner_area = block.inner(full_area);
-            block.render(full_area, buf);
+            block.render_ref(full_area, buf);
             inner_area
         });
 
+        let area = self.render_bookmarks(area, buf, state);
+
+        // Keep the selection in view when the height changed since the last render
+        let resized = state.last_area.height != area.height;
+        if resized
+            && state
+                .last_rendered_identifiers
+                .iter()
+                .any(|(_, identifier)| *identifier == state.selected)
+        {
+            state.ensure_selected_in_view_on_next_render = true;
+        }
+
         state.last_area = area;
         state.last_rendered_identifiers.clear();
         if area.width < 1 || area.height < 1 {
             return;
         }
 
-        let visible = state.flatten(self.items);
+        let (visible, closest_to_hidden_selected) = self.flatten(state);
+        state.last_hidden_selected =
+            closest_to_hidden_selected.map(|closest| (state.selected.clone(), closest));
         state.last_biggest_index = visible.len().saturating_sub(1);
+        state.last_max_offset = 0;
+
+        // Opened lazy items without children are either loading or waiting to be loaded
+        let mut loading = HashSet::new();
+        state.last_pending_loads.clear();
+        for Flattened { identifier, item } in &visible {
+            if item.lazy && item.children.is_empty() && state.opened.contains(identifier) {
+                if state.loading.contains(&**identifier) {
+                    loading.insert(identifier.to_vec());
+                } else {
+                    state.last_pending_loads.push(identifier.to_vec());
+                }
+            }
+        }
+        state.loading = loading;
+
         if visible.is_empty() {
             return;
         }
         let available_height = area.height as usize;
 
+        // Height of each visible item including its description and the placeholder of loading items
+        let heights = visible
+            .iter()
+            .map(|flattened| self.row_height(flattened, state))
+            .collect::<Vec<_>>();
+
+        // Smallest offset showing the last item without empty space after it
+        state.last_max_offset = heights.len();
+        let mut tail_height = 0;
+        while state.last_max_offset > 0
+            && tail_height + heights[state.last_max_offset - 1] <= available_height
+        {
+            tail_height += heights[state.last_max_offset - 1];
+            state.last_max_offset -= 1;
+        }
+
+        let compact_rows = self.compact_rows(&visible);
+
         let ensure_index_in_view =
             if state.ensure_selected_in_view_on_next_render && !state.selected.is_empty() {
                 visible
@@ -205,18 +995,77 @@ where
         // Ensure last line is still visible
         let mut start = state.offset.min(state.last_biggest_index);
 
+        // Keep the anchor on the same row as on the last render
+        if state.anchor_to_selected {
+            let anchor = state
+                .last_selected_row
+                .as_ref()
+                .filter(|(identifier, _)| *identifier == state.selected)
+                .and_then(|(identifier, row)| {
+                    let index = visible
+                        .iter()
+                        .position(|flattened| flattened.identifier == *identifier)?;
+                    Some((index, usize::from(*row)))
+                });
+            if let Some((index, row)) = anchor {
+                start = index;
+                let mut height = 0;
+                while start > 0 && height + heights[start - 1] <= row {
+                    height += heights[start - 1];
+                    start -= 1;
+                }
+            }
+        }
+
         if let Some(ensure_index_in_view) = ensure_index_in_view {
-            start = start.min(ensure_index_in_view);
+            start =
+                if state.center_selected_on_next_render || self.scroll_mode == ScrollMode::Center {
+                    centered_start(&heights, ensure_index_in_view, available_height)
+                } else {
+                    start.min(ensure_index_in_view)
+                };
+        }
+
+        // Do not leave empty space at the end when the area got taller
+        if resized {
+            start = start.min(state.last_max_offset);
+        }
+
+        // Reveal the children of items opened since the last render
+        let just_opened = std::mem::take(&mut state.just_opened);
+        if self.reveal_on_open != RevealOnOpen::None {
+            for opened in just_opened {
+                let Some(index) = visible
+                    .iter()
+                    .position(|flattened| flattened.identifier == opened)
+                else {
+                    continue;
+                };
+                if index < start {
+                    continue;
+                }
+                let descendants = visible[index + 1..]
+                    .iter()
+                    .take_while(|flattened| flattened.identifier.starts_with(&opened))
+                    .count();
+                let last = match self.reveal_on_open {
+                    RevealOnOpen::None => index,
+                    RevealOnOpen::FirstChild => index + descendants.min(1),
+                    RevealOnOpen::AsManyAsFit => index + descendants,
+                };
+                while start < index
+                    && heights[start..=last].iter().sum::<usize>() > available_height
+                {
+                    start += 1;
+                }
+            }
         }
 
         let mut end = start;
         let mut height = 0;
-        for item_height in visible
-            .iter()
-            .skip(start)
-            .map(|flattened| flattened.item.height())
-        {
-            if height + item_height > available_height {
+        for &item_height in heights.iter().skip(start) {
+            // The first item is always rendered even when it has to be cut off
+            if end > start && height + item_height > available_height {
                 break;
             }
             height += item_height;
@@ -225,19 +1074,22 @@ where
 
         if let Some(ensure_index_in_view) = ensure_index_in_view {
             while ensure_index_in_view >= end {
-                height += visible[end].item.height();
+                height += heights[end];
                 end += 1;
-                while height > available_height {
-                    height = height.saturating_sub(visible[start].item.height());
+                while height > available_height && start < ensure_index_in_view {
+                    height = height.saturating_sub(heights[start]);
                     start += 1;
                 }
             }
         }
+        let height = height.min(available_height);
 
         state.offset = start;
         state.ensure_selected_in_view_on_next_render = false;
+        state.center_selected_on_next_render = false;
 
-        if let Some(scrollbar) = self.scrollbar {
+        // Scrollbar can only be rendered by value. Its clone only copies references and styles.
+        if let Some(scrollbar) = self.scrollbar.clone() {
             let mut scrollbar_state = ScrollbarState::new(visible.len().saturating_sub(height))
                 .position(start)
                 .viewport_content_length(height);
@@ -254,30 +1106,59 @@ where
 
         let blank_symbol = " ".repeat(self.highlight_symbol.width());
 
+        let line_numbers = self.line_numbers.map(|style| {
+            let labels = visible[start..end]
+                .iter()
+                .enumerate()
+                .map(|(index, flattened)| {
+                    (self.line_number_fn.0)(start + index, &flattened.identifier)
+                })
+                .collect::<Vec<_>>();
+            let width = labels.iter().map(|label| label.width()).max().unwrap_or(0);
+            (labels, width, style)
+        });
+
         let mut current_height = 0;
-        let has_selection = !state.selected.is_empty();
+        let bottom = area.bottom();
+        let has_selection = !state.selected.is_empty() || state.selected_bookmark.is_some();
         #[allow(clippy::cast_possible_truncation)]
-        for flattened in visible.iter().skip(state.offset).take(end - start) {
+        for (index, flattened) in visible
+            .iter()
+            .enumerate()
+            .skip(state.offset)
+            .take(end - start)
+        {
             let Flattened { identifier, item } = flattened;
+            let compact_row = compact_rows.get(index);
+            let depth = compact_row.map_or_else(|| flattened.depth(), |row| row.depth);
 
-            let x = area.x;
             let y = area.y + current_height;
-            let height = item.height() as u16;
-            current_height += height;
+            // Items taller than the remaining area are cut off
+            let height = (item.height() as u16).min(bottom - y);
+            current_height = current_height.saturating_add(heights[index] as u16);
 
-            let area = Rect {
-                x,
+            let mut area = Rect {
+                x: area.x,
                 y,
                 width: area.width,
                 height,
             };
+            let row_area = area;
+
+            if let Some((labels, width, style)) = &line_numbers {
+                let label = format!("{:>width$} ", labels[index - start]);
+                let (after_x, _) = buf.set_stringn(area.x, y, label, area.width as usize, *style);
+                area.width -= after_x - area.x;
+                area.x = after_x;
+            }
+            let x = area.x;
 
             let text = &item.text;
             let item_style = text.style;
 
-            let is_selected = state.selected == *identifier;
+            let is_selected = *identifier == state.selected;
             let after_highlight_symbol_x = if has_selection {
-                let symbol = if is_selected {
+                let symbol = if is_selected && self.focused {
                     self.highlight_symbol
                 } else {
                     &blank_symbol
@@ -288,8 +1169,11 @@ where
                 x
             };
 
-            let after_depth_x = {
-                let indent_width = flattened.depth() * 2;
+            let is_header = item.header && flattened.depth() == 0;
+            let after_depth_x = if is_header {
+                after_highlight_symbol_x
+            } else {
+                let indent_width = depth * 2;
                 let (after_indent_x, _) = buf.set_stringn(
                     after_highlight_symbol_x,
                     y,
@@ -297,8 +1181,12 @@ where
                     indent_width,
                     item_style,
                 );
-                let symbol = if item.children.is_empty() {
-                    self.node_no_children_symbol
+                let symbol = if item.children.is_empty() && !item.lazy {
+                    if item.expandable {
+                        self.node_empty_branch_symbol
+                    } else {
+                        self.node_no_children_symbol
+                    }
                 } else if state.opened.contains(identifier) {
                     self.node_open_symbol
                 } else {
@@ -315,24 +1203,130 @@ where
                 width: area.width.saturating_sub(after_depth_x - x),
                 ..area
             };
-            text.render(text_area, buf);
+            let is_editing = state.editing.as_deref() == Some(&**identifier);
+            if !is_editing {
+                if let Some(hidden) = self.hidden_by_show_more(identifier, state) {
+                    Text::styled(format!("… show {hidden} more"), item_style)
+                        .render(text_area, buf);
+                } else if let Some(text) = compact_row.and_then(|row| row.text.as_ref()) {
+                    text.render(text_area, buf);
+                } else if let Some(dynamic_text) = item.dynamic_text() {
+                    dynamic_text.render(text_area, buf);
+                } else {
+                    text.render(text_area, buf);
+                }
+            }
+
+            if is_header {
+                buf.set_style(area, self.header_style);
+            }
 
             if is_selected {
-                buf.set_style(area, self.highlight_style);
+                buf.set_style(area, self.selected_style());
             }
 
-            state
-                .last_rendered_identifiers
-                .push((area.y, identifier.clone()));
-        }
-        state.last_identifiers = visible
-            .into_iter()
-            .map(|flattened| flattened.identifier)
-            .collect();
-    }
-}
+            if is_editing {
+                self.render_edit(text_area, buf);
+            }
+
+            if let Some(row_overlay) = &self.row_overlay {
+                let leaf = item.children.is_empty() && !item.lazy;
+                let context = RowContext {
+                    area: row_area,
+                    identifier,
+                    depth,
+                    selected: is_selected,
+                    open: !leaf && (is_header || state.opened.contains(identifier)),
+                    leaf,
+                };
+                let mut row_buffer = Buffer::empty(row_area);
+                for y in row_area.top()..row_area.bottom() {
+                    for x in row_area.left()..row_area.right() {
+                        *row_buffer.get_mut(x, y) = buf.get(x, y).clone();
+                    }
+                }
+                (row_overlay.0)(&context, &mut row_buffer);
+                buf.merge(&row_buffer);
+            }
+
+            let mut next_y = y + height;
+            if next_y < bottom && self.shows_description(flattened, state) {
+                if let Some((description, style)) =
+                    item.description.as_ref().zip(self.selected_description)
+                {
+                    let description_area = Rect {
+                        x: text_area.x,
+                        y: next_y,
+                        width: text_area.width,
+                        height: 1,
+                    };
+                    buf.set_style(description_area, style);
+                    description
+                        .clone()
+                        .patch_style(style)
+                        .render(description_area, buf);
+                }
+                next_y += 1;
+            }
+
+            let child_row = if state.shows_loading_placeholder(flattened) {
+                Some(&self.loading_placeholder)
+            } else if state.shows_depth_limit(flattened) {
+                Some(&self.depth_limit_marker)
+            } else {
+                None
+            };
+            if let Some(child_row) = child_row.filter(|_| next_y < bottom) {
+                let indent_width = (depth + 1) * 2 + self.node_no_children_symbol.width();
+                let placeholder_x = after_highlight_symbol_x
+                    .saturating_add(indent_width as u16)
+                    .min(area.right());
+                let placeholder_area = Rect {
+                    x: placeholder_x,
+                    y: next_y,
+                    width: area.right() - placeholder_x,
+                    height: 1,
+                };
+                if !placeholder_area.is_empty() {
+                    child_row.clone().render(placeholder_area, buf);
+                }
+            }
+
+            state
+                .last_rendered_identifiers
+                .push((area.y, identifier.to_vec()));
+        }
+        state.last_selected_row = state
+            .last_rendered_identifiers
+            .iter()
+            .find(|(_, identifier)| *identifier == state.selected)
+            .map(|(y, identifier)| (identifier.clone(), y - area.y));
+        state.last_headers = visible
+            .iter()
+            .filter(|flattened| flattened.item.header && flattened.depth() == 0)
+            .map(|flattened| flattened.identifier.to_vec())
+            .collect();
+        state.last_identifiers = visible
+            .into_iter()
+            .map(|flattened| flattened.identifier)
+            .collect();
+        #[cfg(feature = "crossterm")]
+        {
+            state.last_heights = heights;
+        }
+    }
+}
 
 impl<Identifier> Widget for Tree<'_, Identifier>
+where
+    Identifier: Clone + Default + Eq + core::hash::Hash,
+{
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Widget::render(&self, area, buf);
+    }
+}
+
+impl<Identifier> Widget for &Tree<'_, Identifier>
 where
     Identifier: Clone + Default + Eq + core::hash::Hash,
 {
@@ -342,8 +1336,77 @@ where
     }
 }
 
+/// First index to render so the item at `index` is in the vertical center of the available height.
+///
+/// Clamped to not leave empty space at the end.
+fn centered_start(heights: &[usize], index: usize, available_height: usize) -> usize {
+    let lines_above = available_height.saturating_sub(heights[index]) / 2;
+    let mut start = index;
+    let mut height = 0;
+    while start > 0 && height + heights[start - 1] <= lines_above {
+        height += heights[start - 1];
+        start -= 1;
+    }
+
+    // Lowest start which still fills the available height until the last item
+    let mut last_start = heights.len();
+    let mut height = 0;
+    while last_start > 0 && height + heights[last_start - 1] <= available_height {
+        height += heights[last_start - 1];
+        last_start -= 1;
+    }
+
+    start.min(last_start)
+}
+
+/// A visible row when chains are merged by [`Tree::compact_single_child`].
+struct CompactRow<'a> {
+    /// Depth of the row which is less than the depth of its item when parents were merged
+    depth: usize,
+    /// Text of the whole chain when the item was merged with its parents
+    text: Option<Text<'a>>,
+}
+
+/// Whether the item is shown in the same row as its only child by [`Tree::compact_single_child`].
+fn is_chain_link<Identifier>(identifier: &[Identifier], item: &TreeItem<Identifier>) -> bool {
+    let is_header = item.header && identifier.len() == 1;
+    !is_header && item.children.len() == 1 && !item.children[0].children.is_empty()
+}
+
+/// Join the first line of each item of the chain with the separator.
+///
+/// Further lines of the last item are kept.
+fn merged_text<'a, Identifier>(
+    chain: &[&'a TreeItem<'a, Identifier>],
+    item: &'a TreeItem<'a, Identifier>,
+    separator: &'a str,
+) -> Text<'a> {
+    let mut spans = Vec::new();
+    for (index, part) in chain.iter().copied().chain([item]).enumerate() {
+        if index > 0 {
+            spans.push(Span::raw(separator));
+        }
+        if let Some(line) = part.text.lines.first() {
+            let style = part.text.style.patch(line.style);
+            spans.extend(
+                line.spans
+                    .iter()
+                    .map(|span| Span::styled(span.content.clone(), style.patch(span.style))),
+            );
+        }
+    }
+    let mut lines = vec![Line::from(spans)];
+    lines.extend(item.text.lines.iter().skip(1).map(|line| Line {
+        style: item.text.style.patch(line.style),
+        ..line.clone()
+    }));
+    Text::from(lines)
+}
+
 #[cfg(test)]
 mod render_tests {
+    use ratatui::style::Color;
+
     use super::*;
 
     #[must_use]
@@ -365,6 +1428,436 @@ mod render_tests {
         _ = render(10, 10, &mut TreeState::default());
     }
 
+    #[test]
+    fn tiny_areas_do_not_panic() {
+        let mut items = TreeItem::example();
+        items.push(
+            TreeItem::new(
+                "i",
+                "India\nmultiline",
+                vec![TreeItem::new_lazy("j", "Juliett")],
+            )
+            .unwrap(),
+        );
+        let mut state = TreeState::default();
+        for identifier in [vec!["b"], vec!["b", "d"], vec!["i"], vec!["i", "j"]] {
+            state.open(identifier);
+        }
+        state.mark_loading(vec!["i", "j"]);
+        state.select(vec!["b", "d", "f"]);
+
+        let tree = Tree::new(&items)
+            .unwrap()
+            .highlight_symbol(">> ")
+            .line_numbers(true, Style::new())
+            .experimental_scrollbar(Some(Scrollbar::default()));
+        _ = render_items(&items, 20, 3, &mut state);
+        assert_eq!(state.get_offset(), 3);
+        assert_eq!(tree.render_to_string(&state, 0), "");
+
+        for block in [None, Some(Block::bordered())] {
+            for width in 0..=5 {
+                for height in 0..=5 {
+                    let tree = block
+                        .clone()
+                        .map_or_else(|| tree.clone(), |block| tree.clone().block(block));
+                    let area = Rect::new(0, 0, width, height);
+                    let mut buffer = Buffer::empty(area);
+
+                    let mut tiny_state = state.clone();
+                    StatefulWidget::render(tree.clone(), area, &mut buffer, &mut tiny_state);
+                    let inner = block.as_ref().map_or(area, |block| block.inner(area));
+                    if inner.is_empty() {
+                        assert_eq!(tiny_state.get_offset(), state.get_offset());
+                    } else {
+                        assert!(tiny_state.get_offset() < tiny_state.visible_count());
+                    }
+
+                    tiny_state.select(vec!["i", "j"]);
+                    StatefulWidget::render(tree.clone(), area, &mut buffer, &mut tiny_state);
+                    Widget::render(tree, area, &mut buffer);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tall_item_is_cut_off() {
+        let items = vec![
+            TreeItem::new_leaf("a", "Alfa"),
+            TreeItem::new_leaf("b", "Bravo\nmultiline\nitem"),
+        ];
+        let mut state = TreeState::default();
+        state.select(vec!["b"]);
+        let buffer = render_items(&items, 12, 2, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Bravo", "  multiline"]);
+        assert_eq!(state.get_offset(), 1);
+    }
+
+    #[test]
+    fn render_by_reference_twice() {
+        let items = TreeItem::example();
+        let tree = Tree::new(&items)
+            .unwrap()
+            .block(Block::bordered())
+            .highlight_symbol(">> ");
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.select(vec!["b", "c"]);
+        let area = Rect::new(0, 0, 20, 6);
+
+        let mut first = Buffer::empty(area);
+        StatefulWidget::render(&tree, area, &mut first, &mut state);
+        let mut second = Buffer::empty(area);
+        StatefulWidget::render(&tree, area, &mut second, &mut state);
+        assert_eq!(first, second);
+
+        let mut owned = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut owned, &mut state);
+        assert_eq!(first, owned);
+    }
+
+    #[test]
+    fn editing_row() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.select(vec!["b", "c"]);
+        assert!(state.begin_edit());
+        assert_eq!(state.editing(), Some(["b", "c"].as_slice()));
+
+        let tree = Tree::new(&items)
+            .unwrap()
+            .edit_text("Chuck")
+            .edit_cursor(2)
+            .edit_style(Style::new().fg(Color::Yellow));
+        let area = Rect::new(0, 0, 15, 4);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            ["  Alfa", "▼ Bravo", "    Chuck", "  ▶ Delta"]
+        );
+        let cursor = buffer.get(6, 2);
+        assert_eq!(cursor.symbol(), "u");
+        assert!(cursor.modifier.contains(Modifier::REVERSED));
+        assert_eq!(cursor.fg, Color::Yellow);
+        assert!(!buffer.get(5, 2).modifier.contains(Modifier::REVERSED));
+
+        assert!(state.end_edit());
+        assert!(!state.end_edit());
+        StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+        assert_eq!(buffer_lines(&buffer)[2], "    Charlie");
+    }
+
+    #[test]
+    fn editing_row_scrolls_to_cursor() {
+        let items = vec![TreeItem::new_leaf("a", "Alfa")];
+        let mut state = TreeState::default();
+        state.select(vec!["a"]);
+        assert!(state.begin_edit());
+        let tree = Tree::new(&items)
+            .unwrap()
+            .edit_text("abcdefghij")
+            .edit_cursor(10);
+        let buffer = {
+            let area = Rect::new(0, 0, 8, 1);
+            let mut buffer = Buffer::empty(area);
+            StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+            buffer
+        };
+        assert_eq!(buffer_lines(&buffer), ["  fghij"]);
+        assert!(buffer.get(7, 0).modifier.contains(Modifier::REVERSED));
+    }
+
+    #[track_caller]
+    fn selected_row(items: &[TreeItem<usize>], state: &mut TreeState<usize>) -> Option<u16> {
+        let tree = Tree::new(items).unwrap();
+        let area = Rect::new(0, 0, 10, 10);
+        StatefulWidget::render(tree, area, &mut Buffer::empty(area), state);
+        state
+            .last_rendered_identifiers
+            .iter()
+            .find(|(_, identifier)| identifier == state.selected())
+            .map(|(y, _)| *y)
+    }
+
+    #[test]
+    fn anchor_to_selected() {
+        let mut items = (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let mut state = TreeState::default();
+        state.anchor_to_selected(true);
+        state.select(vec![5]);
+        assert_eq!(selected_row(&items, &mut state), Some(5));
+
+        items.insert(0, TreeItem::new_leaf(100, "new"));
+        items.insert(0, TreeItem::new_leaf(101, "newer"));
+        assert_eq!(selected_row(&items, &mut state), Some(5));
+        assert_eq!(state.get_offset(), 2);
+
+        items.drain(..3);
+        assert_eq!(selected_row(&items, &mut state), Some(4));
+        assert_eq!(state.get_offset(), 0);
+    }
+
+    #[test]
+    fn anchor_disappeared() {
+        let mut items = (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let mut state = TreeState::default();
+        state.anchor_to_selected(true);
+        state.select(vec![5]);
+        assert_eq!(selected_row(&items, &mut state), Some(5));
+
+        items.remove(5);
+        items.insert(0, TreeItem::new_leaf(100, "new"));
+        assert_eq!(selected_row(&items, &mut state), None);
+        assert_eq!(state.get_offset(), 0);
+    }
+
+    #[test]
+    fn without_anchor_rows_move() {
+        let mut items = (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let mut state = TreeState::default();
+        state.select(vec![5]);
+        assert_eq!(selected_row(&items, &mut state), Some(5));
+
+        items.insert(0, TreeItem::new_leaf(100, "new"));
+        items.insert(0, TreeItem::new_leaf(101, "newer"));
+        assert_eq!(selected_row(&items, &mut state), Some(7));
+    }
+
+    #[test]
+    fn pagination() {
+        let children = ["c0", "c1", "c2", "c3", "c4"]
+            .into_iter()
+            .map(|identifier| TreeItem::new_leaf(identifier, identifier))
+            .collect();
+        let items = vec![
+            TreeItem::new("p", "Paginated", children)
+                .unwrap()
+                .paginated(2, "more")
+                .unwrap(),
+            TreeItem::new_leaf("z", "Zulu"),
+        ];
+        let mut state = TreeState::default();
+        state.open(vec!["p"]);
+        let buffer = render_items(&items, 20, 8, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            [
+                "▼ Paginated",
+                "    c0",
+                "    c1",
+                "    … show 3 more",
+                "  Zulu",
+                "",
+                "",
+                ""
+            ]
+        );
+        assert_eq!(state.visible_count(), 5);
+        assert_eq!(TreeState::total_count(&items), 7);
+
+        state.select(vec!["p", "more"]);
+        assert_eq!(state.enter(&items), Enter::NextPage);
+        assert_eq!(state.selected(), ["p", "c2"]);
+        let buffer = render_items(&items, 20, 8, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer)[3..6],
+            ["    c2", "    c3", "    … show 1 more"]
+        );
+        assert_eq!(state.visible_count(), 7);
+
+        state.select(vec!["p", "more"]);
+        assert_eq!(state.enter(&items), Enter::NextPage);
+        _ = render_items(&items, 20, 8, &mut state);
+        assert_eq!(state.visible_count(), 7);
+        assert_eq!(state.selected(), ["p", "c4"]);
+        assert_eq!(state.selected_position(), Some(6));
+
+        state.reset_pagination();
+        _ = render_items(&items, 20, 8, &mut state);
+        assert_eq!(state.visible_count(), 5);
+    }
+
+    #[test]
+    fn dynamic_leaf_only_evaluated_when_visible() {
+        let calls = Rc::new(std::cell::Cell::new(0));
+        let items = (0..20)
+            .map(|index| {
+                let calls = Rc::clone(&calls);
+                TreeItem::new_dynamic_leaf(index, move || {
+                    calls.set(calls.get() + 1);
+                    Line::from(format!("call {}", calls.get()))
+                })
+            })
+            .collect::<Vec<_>>();
+        let tree = Tree::new(&items).unwrap();
+        let area = Rect::new(0, 0, 10, 3);
+        let mut state = TreeState::default();
+
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+        assert_eq!(calls.get(), 3);
+        assert_eq!(buffer_lines(&buffer), ["  call 1", "  call 2", "  call 3"]);
+
+        state.scroll_down(10);
+        StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+        assert_eq!(calls.get(), 6);
+        assert_eq!(buffer_lines(&buffer)[0], "  call 4");
+    }
+
+    #[track_caller]
+    fn render_bookmarks(state: &mut TreeState<&'static str>) -> Vec<String> {
+        let items = TreeItem::example();
+        let tree = Tree::new(&items)
+            .unwrap()
+            .highlight_symbol(">")
+            .show_bookmarks(true, Style::new());
+        let area = Rect::new(0, 0, 15, 7);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn bookmarks() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        assert_eq!(
+            render_bookmarks(&mut state),
+            ["  Alfa", "▶ Bravo", "  Hotel", "", "", "", ""]
+        );
+
+        state.select(vec!["b", "d", "e"]);
+        assert!(state.toggle_bookmark_selected());
+        state.select(vec!["h"]);
+        assert!(state.toggle_bookmark_selected());
+        assert_eq!(state.bookmarks(), [vec!["b", "d", "e"], vec!["h"]]);
+        assert_eq!(
+            render_bookmarks(&mut state),
+            [
+                "Bookmarks",
+                "   Echo",
+                "   Hotel",
+                "   Alfa",
+                " ▶ Bravo",
+                ">  Hotel",
+                ""
+            ]
+        );
+
+        // Navigation flows from the tree into the bookmarks
+        assert!(state.key_up());
+        assert!(state.key_up());
+        assert!(state.key_up());
+        assert_eq!(state.selected_bookmark(), Some(["h"].as_slice()));
+        assert!(state.selected().is_empty());
+        assert!(state.key_up());
+        assert!(!state.key_up());
+        assert_eq!(state.selected_bookmark(), Some(["b", "d", "e"].as_slice()));
+        assert_eq!(render_bookmarks(&mut state)[1], ">  Echo");
+
+        assert_eq!(state.enter(&items), Enter::Jumped);
+        assert_eq!(state.selected(), ["b", "d", "e"]);
+        assert_eq!(state.selected_bookmark(), None);
+        assert!(state.opened().contains(&["b"]));
+        assert!(state.opened().contains(&["b", "d"]));
+        assert_eq!(
+            render_bookmarks(&mut state),
+            [
+                "Bookmarks",
+                "   Echo",
+                "   Hotel",
+                " ▼ Bravo",
+                "     Charlie",
+                "   ▼ Delta",
+                ">      Echo"
+            ]
+        );
+
+        state.select(vec!["h"]);
+        assert!(!state.toggle_bookmark_selected());
+        assert_eq!(state.bookmarks(), [vec!["b", "d", "e"]]);
+    }
+
+    #[test]
+    fn stale_bookmarks_are_skipped() {
+        let mut state = TreeState::default();
+        state.select(vec!["gone"]);
+        state.toggle_bookmark_selected();
+        state.select(vec!["a"]);
+        state.toggle_bookmark_selected();
+        let lines = render_bookmarks(&mut state);
+        assert_eq!(lines[..3], ["Bookmarks", "   Alfa", ">  Alfa"]);
+
+        assert!(state.key_up());
+        assert!(!state.key_up());
+        assert_eq!(state.selected_bookmark(), Some(["a"].as_slice()));
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["a"]);
+    }
+
+    #[track_caller]
+    fn render_descriptions(state: &mut TreeState<&'static str>) -> Vec<String> {
+        let items = vec![
+            TreeItem::new_leaf("a", "Alfa").description("first"),
+            TreeItem::new_leaf("b", "Bravo"),
+            TreeItem::new_leaf("c", "Charlie").description("third"),
+        ];
+        let tree = Tree::new(&items)
+            .unwrap()
+            .show_selected_description(true, Style::new().fg(Color::Gray));
+        let area = Rect::new(0, 0, 15, 3);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn selected_description() {
+        let mut state = TreeState::default();
+        assert_eq!(
+            render_descriptions(&mut state),
+            ["  Alfa", "  Bravo", "  Charlie"]
+        );
+
+        state.select(vec!["a"]);
+        assert_eq!(
+            render_descriptions(&mut state),
+            ["  Alfa", "  first", "  Bravo"]
+        );
+
+        state.select(vec!["c"]);
+        assert_eq!(
+            render_descriptions(&mut state),
+            ["  Bravo", "  Charlie", "  third"]
+        );
+        assert_eq!(state.get_offset(), 1);
+    }
+
+    #[test]
+    fn without_description_nothing_changes() {
+        let mut state = TreeState::default();
+        state.select(vec!["b"]);
+        assert_eq!(
+            render_descriptions(&mut state),
+            ["  Alfa", "  Bravo", "  Charlie"]
+        );
+
+        let items = vec![TreeItem::new_leaf("a", "Alfa").description("first")];
+        let mut state = TreeState::default();
+        state.select(vec!["a"]);
+        let buffer = render_items(&items, 10, 2, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", ""]);
+    }
+
     #[test]
     fn nothing_open() {
         let buffer = render(10, 4, &mut TreeState::default());
@@ -414,4 +1907,914 @@ mod render_tests {
         ]);
         assert_eq!(buffer, expected);
     }
+
+    #[track_caller]
+    fn buffer_lines(buffer: &Buffer) -> Vec<String> {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                let line = (area.left()..area.right())
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect::<String>();
+                line.trim_end().to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn render_to_string_matches_buffer() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        state.select(vec!["b", "d", "e"]);
+        let items = TreeItem::example();
+        let tree = Tree::new(&items).unwrap().highlight_symbol(">> ");
+        let text = tree.render_to_string(&state, 15);
+
+        let area = Rect::new(0, 0, 15, 8);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(text, buffer_lines(&buffer).join("\n"));
+        assert_eq!(text.lines().count(), 8);
+    }
+
+    #[test]
+    fn render_to_string_truncates() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        let items = TreeItem::example();
+        let tree = Tree::new(&items).unwrap();
+        let text = tree.render_to_string(&state, 5);
+        assert_eq!(text, "  Alf\n▼ Bra\n    C\n  ▶ D\n    G\n  Hot");
+
+        let area = Rect::new(0, 0, 5, 6);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(text, buffer_lines(&buffer).join("\n"));
+    }
+
+    #[test]
+    fn render_to_string_ignores_offset() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        let items = TreeItem::example();
+        let tree = Tree::new(&items).unwrap();
+        _ = render(10, 2, &mut state);
+        state.scroll_down(3);
+        let text = tree.render_to_string(&state, 10);
+        assert_eq!(text.lines().count(), 6);
+        assert_eq!(state.get_offset(), 3);
+    }
+
+    #[test]
+    fn render_to_string_renders_multiple_pages() {
+        let items = (0..200)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let state = TreeState::default();
+        let text = Tree::new(&items).unwrap().render_to_string(&state, 1000);
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 200);
+        assert_eq!(lines[199], "  199");
+    }
+
+    #[must_use]
+    #[track_caller]
+    fn render_items(
+        items: &[TreeItem<'static, &'static str>],
+        width: u16,
+        height: u16,
+        state: &mut TreeState<&'static str>,
+    ) -> Buffer {
+        let tree = Tree::new(items).unwrap();
+        let area = Rect::new(0, 0, width, height);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer
+    }
+
+    #[test]
+    fn lazy_loading() {
+        let mut items = vec![
+            TreeItem::new_leaf("a", "Alfa"),
+            TreeItem::new_lazy("l", "Lazy"),
+        ];
+        let mut state = TreeState::default();
+        let buffer = render_items(&items, 12, 3, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", "▶ Lazy", ""]);
+        assert!(state.pending_loads().is_empty());
+
+        state.open(vec!["l"]);
+        let buffer = render_items(&items, 12, 3, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", "▼ Lazy", ""]);
+        assert_eq!(state.pending_loads(), [vec!["l"]]);
+
+        assert!(state.mark_loading(vec!["l"]));
+        assert!(state.pending_loads().is_empty());
+        let buffer = render_items(&items, 12, 3, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", "▼ Lazy", "    Loading…"]);
+
+        TreeItem::set_children_at(&mut items, &["l"], vec![TreeItem::new_leaf("c", "Child")])
+            .unwrap();
+        let buffer = render_items(&items, 12, 3, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", "▼ Lazy", "    Child"]);
+        assert!(state.pending_loads().is_empty());
+        assert!(state.loading.is_empty());
+    }
+
+    #[test]
+    fn lazy_closed_before_loaded() {
+        let items = vec![TreeItem::new_lazy("l", "Lazy")];
+        let mut state = TreeState::default();
+        state.open(vec!["l"]);
+        _ = render_items(&items, 12, 3, &mut state);
+        assert_eq!(state.pending_loads(), [vec!["l"]]);
+        state.close(&["l"]);
+        assert!(state.pending_loads().is_empty());
+
+        state.open(vec!["l"]);
+        state.mark_loading(vec!["l"]);
+        state.select(vec!["l"]);
+        state.key_left();
+        let buffer = render_items(&items, 12, 3, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["▶ Lazy", "", ""]);
+        assert!(state.loading.is_empty());
+        assert!(state.pending_loads().is_empty());
+    }
+
+    #[test]
+    fn loading_placeholder_counts_as_height() {
+        let items = vec![
+            TreeItem::new_lazy("l", "Lazy"),
+            TreeItem::new_leaf("a", "Alfa"),
+        ];
+        let mut state = TreeState::default();
+        state.open(vec!["l"]);
+        state.mark_loading(vec!["l"]);
+        state.select(vec!["a"]);
+        let buffer = render_items(&items, 12, 2, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["  Alfa", ""]);
+        assert_eq!(state.get_offset(), 1);
+    }
+
+    #[test]
+    fn line_numbers() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.select(vec!["b"]);
+        let area = Rect::new(0, 0, 12, 4);
+        let mut buffer = Buffer::empty(area);
+        let tree = Tree::new(&items)
+            .unwrap()
+            .line_numbers(true, Style::new().fg(Color::DarkGray))
+            .highlight_style(Style::new().bg(Color::Blue));
+        StatefulWidget::render(tree.clone(), area, &mut buffer, &mut state);
+        let mut expected = Buffer::with_lines([
+            "1   Alfa    ",
+            "2 ▶ Bravo   ",
+            "3   Hotel   ",
+            "            ",
+        ]);
+        // The separating space is part of the line number
+        expected.set_style(Rect::new(0, 0, 2, 3), Style::new().fg(Color::DarkGray));
+        expected.set_style(Rect::new(2, 1, 10, 1), Style::new().bg(Color::Blue));
+        assert_eq!(buffer, expected);
+
+        // Line numbers change with the opened nodes
+        state.open(vec!["b"]);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            ["1   Alfa", "2 ▼ Bravo", "3     Charli", "4   ▶ Delta"]
+        );
+    }
+
+    #[test]
+    fn line_numbers_width_is_widest_on_screen() {
+        let items = (0..12)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let tree = Tree::new(&items).unwrap().line_numbers(true, Style::new());
+        let mut state = TreeState::default();
+
+        let area = Rect::new(0, 0, 10, 3);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree.clone(), area, &mut buffer, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["1   0", "2   1", "3   2"]);
+
+        state.scroll_down(8);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(buffer_lines(&buffer), [" 9   8", "10   9", "11   10"]);
+    }
+
+    #[test]
+    fn line_number_fn() {
+        let items = TreeItem::example();
+        let tree = Tree::new(&items)
+            .unwrap()
+            .line_numbers(true, Style::new())
+            .line_number_fn(|index, identifier| format!("{index}{}", identifier.join("/")));
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        let area = Rect::new(0, 0, 14, 3);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            ["  0a   Alfa", "  1b ▼ Bravo", "2b/c     Charl"]
+        );
+    }
+
+    #[test]
+    fn wrap_down_to_top_scrolls() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        state.set_wrap(true);
+        _ = render(10, 3, &mut state);
+        assert!(state.select_last());
+        _ = render(10, 3, &mut state);
+        assert_eq!(state.get_offset(), 5);
+
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["a"]);
+        _ = render(10, 3, &mut state);
+        assert_eq!(state.get_offset(), 0);
+    }
+
+    #[test]
+    fn wrap_up_to_bottom_scrolls() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        state.set_wrap(true);
+        _ = render(10, 3, &mut state);
+        assert!(state.select_first());
+
+        assert!(state.key_up());
+        assert_eq!(state.selected(), ["h"]);
+        _ = render(10, 3, &mut state);
+        assert_eq!(state.get_offset(), 5);
+    }
+
+    #[test]
+    fn no_wrap_stays_at_ends() {
+        let mut state = TreeState::default();
+        _ = render(10, 3, &mut state);
+        assert!(state.select_first());
+        assert!(!state.key_up());
+        assert_eq!(state.selected(), ["a"]);
+        assert!(state.select_last());
+        assert!(!state.key_down());
+        assert_eq!(state.selected(), ["h"]);
+    }
+
+    #[track_caller]
+    fn render_numbers(scroll_mode: ScrollMode, state: &mut TreeState<usize>) {
+        let items = (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let tree = Tree::new(&items).unwrap().scroll_mode(scroll_mode);
+        let area = Rect::new(0, 0, 10, 5);
+        StatefulWidget::render(tree, area, &mut Buffer::empty(area), state);
+    }
+
+    #[test]
+    fn center_selected() {
+        let mut state = TreeState::default();
+        render_numbers(ScrollMode::Minimal, &mut state);
+
+        state.select(vec![10]);
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 6);
+        state.center_selected();
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 8);
+
+        // Only once
+        state.key_down();
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 8);
+    }
+
+    #[test]
+    fn center_selected_clamps() {
+        let mut state = TreeState::default();
+        render_numbers(ScrollMode::Minimal, &mut state);
+
+        state.select(vec![1]);
+        state.center_selected();
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 0);
+
+        state.select(vec![18]);
+        state.center_selected();
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 15);
+    }
+
+    #[test]
+    fn scroll_mode_center() {
+        let mut state = TreeState::default();
+        render_numbers(ScrollMode::Center, &mut state);
+
+        let mut offsets = Vec::new();
+        for _ in 0..20 {
+            state.key_down();
+            render_numbers(ScrollMode::Center, &mut state);
+            offsets.push(state.get_offset());
+        }
+        assert_eq!(
+            offsets,
+            [0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 15, 15]
+        );
+
+        // Scrolling without navigation is not reverted
+        state.scroll_up(5);
+        render_numbers(ScrollMode::Center, &mut state);
+        assert_eq!(state.get_offset(), 10);
+    }
+
+    #[test]
+    fn counts() {
+        fn walk(
+            items: &[TreeItem<&'static str>],
+            state: &TreeState<&'static str>,
+            parent: &[&'static str],
+            visible: &mut Vec<Vec<&'static str>>,
+        ) -> usize {
+            let mut total = 0;
+            for item in items {
+                let mut identifier = parent.to_vec();
+                identifier.push(item.identifier);
+                total += 1;
+                let opened = state.opened().contains(&identifier);
+                visible.push(identifier.clone());
+                let mut hidden = Vec::new();
+                let children = if opened { &mut *visible } else { &mut hidden };
+                total += walk(&item.children, state, &identifier, children);
+            }
+            total
+        }
+
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.select(vec!["b", "g"]);
+        _ = render(20, 3, &mut state);
+
+        let mut visible = Vec::new();
+        let total = walk(&items, &state, &[], &mut visible);
+        assert_eq!(TreeState::total_count(&items), total);
+        assert_eq!(state.visible_count(), visible.len());
+        let position = visible
+            .iter()
+            .position(|identifier| identifier == state.selected());
+        assert_eq!(state.selected_position(), position.map(|index| index + 1));
+        assert_eq!(state.visible_count(), 6);
+        assert_eq!(state.selected_position(), Some(5));
+
+        state.select(Vec::new());
+        assert_eq!(state.selected_position(), None);
+
+        state.close(&["b"]);
+        _ = render(20, 3, &mut state);
+        assert_eq!(state.visible_count(), 3);
+        assert_eq!(TreeState::total_count(&items), 8);
+    }
+
+    #[track_caller]
+    fn render_reveal(reveal_on_open: RevealOnOpen) -> Vec<String> {
+        let children = vec![
+            TreeItem::new_leaf("c", "Charlie"),
+            TreeItem::new_leaf("d", "Delta"),
+            TreeItem::new_leaf("e", "Echo"),
+        ];
+        let items = vec![
+            TreeItem::new_leaf("a", "Alfa"),
+            TreeItem::new_leaf("b", "Bravo"),
+            TreeItem::new("x", "X-ray", children).unwrap(),
+        ];
+        let tree = Tree::new(&items).unwrap().reveal_on_open(reveal_on_open);
+        let area = Rect::new(0, 0, 12, 3);
+        let mut state = TreeState::default();
+        StatefulWidget::render(&tree, area, &mut Buffer::empty(area), &mut state);
+        state.select(vec!["x"]);
+        state.toggle_selected();
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(&tree, area, &mut buffer, &mut state);
+
+        // Only directly after opening
+        state.scroll_up(5);
+        StatefulWidget::render(&tree, area, &mut Buffer::empty(area), &mut state);
+        assert_eq!(state.get_offset(), 0);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn reveal_on_open_none() {
+        assert_eq!(
+            render_reveal(RevealOnOpen::None),
+            ["  Alfa", "  Bravo", "▼ X-ray"]
+        );
+    }
+
+    #[test]
+    fn reveal_on_open_first_child() {
+        assert_eq!(
+            render_reveal(RevealOnOpen::FirstChild),
+            ["  Bravo", "▼ X-ray", "    Charlie"]
+        );
+    }
+
+    #[test]
+    fn reveal_on_open_as_many_as_fit() {
+        assert_eq!(
+            render_reveal(RevealOnOpen::AsManyAsFit),
+            ["▼ X-ray", "    Charlie", "    Delta"]
+        );
+    }
+
+    #[test]
+    fn row_overlay_paints_branch_rows() {
+        let items = TreeItem::example();
+        let tree = Tree::new(&items).unwrap().row_overlay(|row, buf| {
+            if !row.leaf {
+                let marker = if row.open { "o" } else { "c" };
+                buf.set_string(row.area.right() - 1, row.area.y, marker, Style::new());
+            }
+        });
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        let area = Rect::new(0, 0, 12, 5);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            [
+                "  Alfa",
+                "▼ Bravo    o",
+                "    Charlie",
+                "  ▶ Delta  c",
+                "    Golf",
+            ]
+        );
+    }
+
+    #[track_caller]
+    fn render_height(height: u16, state: &mut TreeState<usize>) -> Vec<String> {
+        let items = (0..40)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let tree = Tree::new(&items).unwrap();
+        let area = Rect::new(0, 0, 10, height);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn height_change_keeps_selection_visible() {
+        let mut state = TreeState::default();
+        _ = render_height(30, &mut state);
+        state.select(vec![25]);
+        _ = render_height(30, &mut state);
+        assert_eq!(state.get_offset(), 0);
+
+        let lines = render_height(10, &mut state);
+        assert_eq!(lines.last().unwrap(), "  25");
+        assert_eq!(state.get_offset(), 16);
+
+        assert!(state.key_page_down());
+        assert_eq!(state.selected(), [35]);
+        let lines = render_height(10, &mut state);
+        assert_eq!(lines.last().unwrap(), "  35");
+        assert_eq!(state.get_offset(), 26);
+
+        assert!(state.key_page_down());
+        assert_eq!(state.selected(), [39]);
+        _ = render_height(10, &mut state);
+        assert_eq!(state.get_offset(), 30);
+
+        assert!(state.key_page_up());
+        assert_eq!(state.selected(), [29]);
+        let lines = render_height(30, &mut state);
+        assert!(lines.contains(&"  29".to_owned()));
+        assert_eq!(state.get_offset(), 10);
+    }
+
+    #[track_caller]
+    fn render_hidden(hide: bool, state: &mut TreeState<&'static str>) -> Vec<String> {
+        let items = TreeItem::example();
+        let tree = Tree::new(&items)
+            .unwrap()
+            .hide_fn(move |identifier, _item| hide && identifier.last() == Some(&"d"));
+        let area = Rect::new(0, 0, 15, 8);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn hide_fn_hides_with_descendants() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        _ = render_hidden(false, &mut state);
+        assert_eq!(state.visible_count(), 8);
+
+        assert_eq!(
+            render_hidden(true, &mut state),
+            [
+                "  Alfa",
+                "▼ Bravo",
+                "    Charlie",
+                "    Golf",
+                "  Hotel",
+                "",
+                "",
+                ""
+            ]
+        );
+        assert_eq!(state.visible_count(), 5);
+        for _ in 0..4 {
+            assert!(state.key_down());
+        }
+        assert_eq!(state.selected(), ["b", "g"]);
+
+        _ = render_hidden(false, &mut state);
+        assert_eq!(state.visible_count(), 8);
+        assert_eq!(state.selected(), ["b", "g"]);
+    }
+
+    #[test]
+    fn hide_fn_recovers_hidden_selection() {
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.open(vec!["b", "d"]);
+        state.select(vec!["b", "d", "e"]);
+        _ = render_hidden(false, &mut state);
+
+        state.set_undo_capacity(10);
+        _ = render_hidden(true, &mut state);
+        assert_eq!(state.selected(), ["b", "d", "e"]);
+        assert!(!state.undo());
+
+        // Showing it again keeps the selection
+        _ = render_hidden(false, &mut state);
+        assert_eq!(state.selected(), ["b", "d", "e"]);
+
+        _ = render_hidden(true, &mut state);
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["b", "c"]);
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["b", "g"]);
+    }
+
+    #[test]
+    fn hide_fn_hiding_everything_keeps_selection() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.select(vec!["b"]);
+        let tree = Tree::new(&items).unwrap().hide_fn(|_, _| true);
+        let area = Rect::new(0, 0, 15, 3);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["", "", ""]);
+        assert_eq!(state.selected(), ["b"]);
+    }
+
+    #[test]
+    fn empty_branch_symbol() {
+        let items = vec![
+            TreeItem::new_leaf("e", "Empty").expandable(true),
+            TreeItem::new_leaf("l", "Leaf"),
+        ];
+        let mut state = TreeState::default();
+        let buffer = render_items(&items, 10, 2, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["▶ Empty", "  Leaf"]);
+
+        let tree = Tree::new(&items).unwrap().empty_branch_symbol("◇ ");
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(buffer_lines(&buffer), ["◇ Empty", "  Leaf"]);
+    }
+
+    #[test]
+    fn scroll_ratio() {
+        let mut state = TreeState::default();
+        assert_eq!(state.scroll_ratio(), None);
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.scroll_ratio(), Some(0.0));
+
+        assert!(state.scroll_to_ratio(1.0));
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 15);
+        assert_eq!(state.scroll_ratio(), Some(1.0));
+
+        assert!(state.scroll_to_ratio(0.5));
+        render_numbers(ScrollMode::Minimal, &mut state);
+        assert_eq!(state.get_offset(), 8);
+        assert_eq!(state.scroll_ratio(), Some(8.0 / 15.0));
+
+        assert!(state.scroll_to_ratio(7.0));
+        assert_eq!(state.get_offset(), 15);
+        assert!(state.scroll_to_ratio(-1.0));
+        assert_eq!(state.get_offset(), 0);
+
+        // Scrolled past the end
+        state.scroll_down(19);
+        assert_eq!(state.scroll_ratio(), Some(1.0));
+    }
+
+    #[test]
+    fn scroll_ratio_everything_fits() {
+        let mut state = TreeState::default();
+        _ = render(10, 5, &mut state);
+        assert_eq!(state.scroll_ratio(), None);
+        assert!(!state.scroll_to_ratio(1.0));
+        assert_eq!(state.get_offset(), 0);
+    }
+
+    #[test]
+    fn render_signature() {
+        let mut items = (0..20)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let mut state = TreeState::default();
+        render_numbers(ScrollMode::Minimal, &mut state);
+        let signature = state.render_signature(&items);
+        assert_eq!(state.render_signature(&items), signature);
+
+        // Off-screen
+        items[10] = TreeItem::new_leaf(10, "ten");
+        assert_eq!(state.render_signature(&items), signature);
+
+        // Visible
+        items[4] = TreeItem::new_leaf(4, "four");
+        let edited = state.render_signature(&items);
+        assert_ne!(edited, signature);
+
+        // Opened below the view
+        items[15] = TreeItem::new(15, "15", vec![TreeItem::new_leaf(0, "0")]).unwrap();
+        state.open(vec![15]);
+        assert_eq!(state.render_signature(&items), edited);
+
+        state.scroll_down(1);
+        assert_ne!(state.render_signature(&items), edited);
+        state.scroll_up(1);
+        assert_eq!(state.render_signature(&items), edited);
+
+        state.select(vec![2]);
+        assert_ne!(state.render_signature(&items), edited);
+    }
+
+    #[test]
+    fn very_deep_tree() {
+        const DEPTH: usize = 100_000;
+        let mut item = TreeItem::new_leaf(0, "leaf");
+        for depth in 1..DEPTH {
+            item = TreeItem::new(depth, "nested", vec![item]).unwrap();
+        }
+        let items = vec![item];
+        assert_eq!(TreeState::total_count(&items), DEPTH);
+
+        let mut state = TreeState::default();
+        assert_eq!(
+            state.open_recursive(&items, &[DEPTH - 1], usize::MAX),
+            DEPTH - 1
+        );
+        let area = Rect::new(0, 0, 16, 5);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(Tree::new(&items).unwrap(), area, &mut buffer, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            [
+                "▼ nested",
+                "  ▼ nested",
+                "    ▼ nested",
+                "      ▼ nested",
+                "        ▼ nested"
+            ]
+        );
+
+        // The leaf is indented beyond the area
+        assert!(state.select_last());
+        StatefulWidget::render(Tree::new(&items).unwrap(), area, &mut buffer, &mut state);
+        assert_eq!(state.selected().len(), DEPTH);
+        assert_eq!(state.selected().last(), Some(&0));
+        assert_eq!(buffer_lines(&buffer), [""; 5]);
+    }
+
+    fn roots() -> Vec<TreeItem<'static, &'static str>> {
+        let root = |identifier, text| {
+            let children = vec![
+                TreeItem::new("a", "Alfa", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+                TreeItem::new_leaf("b", "Bravo"),
+            ];
+            TreeItem::new_header(identifier, text, children).unwrap()
+        };
+        vec![root("0", "first"), root("1", "second")]
+    }
+
+    #[test]
+    fn headers() {
+        let items = roots();
+        let mut state = TreeState::default();
+        state.open(vec!["0", "a"]);
+        let buffer = render_items(&items, 12, 7, &mut state);
+        assert_eq!(
+            buffer_lines(&buffer),
+            [
+                "first",
+                "  ▼ Alfa",
+                "      X-ray",
+                "    Bravo",
+                "second",
+                "  ▶ Alfa",
+                "    Bravo",
+            ]
+        );
+
+        // Headers stay open
+        state.close(&["0"]);
+        _ = render_items(&items, 12, 7, &mut state);
+        assert_eq!(state.visible_count(), 7);
+    }
+
+    #[test]
+    fn headers_navigation_crosses_roots() {
+        let items = roots();
+        let mut state = TreeState::default();
+        _ = render_items(&items, 12, 6, &mut state);
+        assert!(state.select_last());
+        assert_eq!(state.selected(), ["1", "b"]);
+        for _ in 0..3 {
+            assert!(state.key_up());
+        }
+        assert_eq!(state.selected(), ["0", "b"]);
+
+        state.set_skip_headers(true);
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["1", "a"]);
+        assert!(state.key_right());
+        _ = render_items(&items, 12, 6, &mut state);
+        assert!(!state.opened().contains(&["0", "a"]));
+        assert!(state.select_first());
+        assert_eq!(state.selected(), ["0", "a"]);
+        assert!(!state.key_up());
+        assert_eq!(state.selected(), ["0", "a"]);
+    }
+
+    fn render_compact(state: &mut TreeState<&'static str>) -> Vec<String> {
+        let items = vec![
+            TreeItem::new(
+                "com",
+                "com",
+                vec![TreeItem::new(
+                    "example",
+                    "example",
+                    vec![TreeItem::new(
+                        "app",
+                        "app",
+                        vec![
+                            TreeItem::new_leaf("main", "main"),
+                            TreeItem::new_leaf("util", "util"),
+                        ],
+                    )
+                    .unwrap()],
+                )
+                .unwrap()],
+            )
+            .unwrap(),
+            TreeItem::new(
+                "src",
+                "src",
+                vec![
+                    TreeItem::new("lib", "lib", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+                    TreeItem::new_leaf("readme", "readme"),
+                ],
+            )
+            .unwrap(),
+        ];
+        let tree = Tree::new(&items).unwrap().compact_single_child(true, " / ");
+        let area = Rect::new(0, 0, 22, 5);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, state);
+        buffer_lines(&buffer)
+    }
+
+    #[test]
+    fn compact_single_child_merges_chain() {
+        let mut state = TreeState::default();
+        assert_eq!(
+            render_compact(&mut state),
+            ["▶ com / example / app", "▶ src", "", "", ""]
+        );
+        assert_eq!(state.visible_count(), 2);
+
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["com", "example", "app"]);
+        assert!(state.toggle_selected());
+        assert_eq!(state.opened().len(), 1);
+        assert_eq!(
+            render_compact(&mut state),
+            ["▼ com / example / app", "    main", "    util", "▶ src", ""]
+        );
+
+        assert!(state.key_down());
+        assert_eq!(state.selected(), ["com", "example", "app", "main"]);
+        assert!(state.key_left());
+        assert_eq!(state.selected(), ["com", "example", "app"]);
+        assert!(state.key_left());
+        assert!(state.opened().is_empty());
+        _ = render_compact(&mut state);
+        assert!(state.key_left());
+        assert!(state.selected().is_empty());
+    }
+
+    #[test]
+    fn compact_single_child_does_not_merge_mixed() {
+        let mut state = TreeState::default();
+        state.open(vec!["src"]);
+        state.open(vec!["src", "lib"]);
+        assert_eq!(
+            render_compact(&mut state),
+            [
+                "▶ com / example / app",
+                "▼ src",
+                "  ▼ lib",
+                "      X-ray",
+                "    readme"
+            ]
+        );
+        assert!(state.select_last());
+        assert_eq!(state.selected(), ["src", "readme"]);
+        assert!(state.key_up());
+        assert_eq!(state.selected(), ["src", "lib", "x"]);
+    }
+
+    #[test]
+    fn unfocused_highlight_style() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.select(vec!["b"]);
+        let area = Rect::new(0, 0, 12, 3);
+        let render = |focused: bool, state: &mut TreeState<&'static str>| {
+            let tree = Tree::new(&items)
+                .unwrap()
+                .highlight_symbol(">")
+                .highlight_style(Style::new().bg(Color::Blue))
+                .unfocused_highlight_style(Style::new().bg(Color::DarkGray))
+                .focused(focused);
+            let mut buffer = Buffer::empty(area);
+            StatefulWidget::render(tree, area, &mut buffer, state);
+            buffer
+        };
+
+        let focused = render(true, &mut state);
+        assert_eq!(buffer_lines(&focused), ["   Alfa", ">▶ Bravo", "   Hotel"]);
+        assert_eq!(focused.get(3, 1).bg, Color::Blue);
+        assert_eq!(focused.get(3, 0).bg, Color::Reset);
+
+        let unfocused = render(false, &mut state);
+        assert_eq!(
+            buffer_lines(&unfocused),
+            ["   Alfa", " ▶ Bravo", "   Hotel"]
+        );
+        assert_eq!(unfocused.get(3, 1).bg, Color::DarkGray);
+        assert_eq!(unfocused.get(3, 0).bg, Color::Reset);
+
+        // Without an unfocused style the selection looks the same
+        let tree = Tree::new(&items)
+            .unwrap()
+            .highlight_style(Style::new().bg(Color::Blue))
+            .focused(false);
+        let mut buffer = Buffer::empty(area);
+        StatefulWidget::render(tree, area, &mut buffer, &mut state);
+        assert_eq!(buffer.get(3, 1).bg, Color::Blue);
+    }
+
+    #[test]
+    fn depth_limit_marker() {
+        let mut state = TreeState::default();
+        state.set_max_path_depth(2);
+        state.open(vec!["b"]);
+        assert!(!state.open(vec!["b", "d"]));
+        assert_eq!(
+            buffer_lines(&render(20, 8, &mut state)),
+            [
+                "  Alfa",
+                "▼ Bravo",
+                "    Charlie",
+                "  ▶ Delta",
+                "      … depth limit",
+                "    Golf",
+                "  Hotel",
+                ""
+            ]
+        );
+        assert_eq!(state.visible_count(), 6);
+    }
 }
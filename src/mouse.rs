@@ -0,0 +1,311 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
+
+use crate::tree_item::TreeItem;
+use crate::tree_state::TreeState;
+
+/// What happened when a [`MouseEvent`] was handled by [`TreeState::handle_mouse`].
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MouseOutcome<Identifier> {
+    /// The event did not change anything.
+    /// For example it happened outside of the last rendered area.
+    Ignored,
+    /// The scroll position changed.
+    Scrolled,
+    /// Another [`TreeItem`] was selected.
+    Selected,
+    /// A branch was double clicked and has been opened / closed.
+    Toggled,
+    /// A leaf was double clicked.
+    /// Apps can use this to open the item, like a file in a file browser.
+    Activated(Vec<Identifier>),
+    /// The show more row of a [paginated](TreeItem::paginated) branch was clicked and the next page is shown.
+    NextPage,
+}
+
+/// Mouse related part of the [`TreeState`].
+#[derive(Debug, Clone)]
+pub struct MouseState<Identifier> {
+    scroll_lines: usize,
+    double_click_interval: Duration,
+    /// Time and identifier of the last left click
+    last_click: Option<(Instant, Vec<Identifier>)>,
+}
+
+impl<Identifier> Default for MouseState<Identifier> {
+    fn default() -> Self {
+        Self {
+            scroll_lines: 3,
+            double_click_interval: Duration::from_millis(500),
+            last_click: None,
+        }
+    }
+}
+
+impl<Identifier> TreeState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Set the amount of lines scrolled by one step of the mouse wheel in [`handle_mouse`](Self::handle_mouse).
+    ///
+    /// Defaults to 3.
+    pub const fn set_mouse_scroll_lines(&mut self, lines: usize) {
+        self.mouse.scroll_lines = lines;
+    }
+
+    /// Set the maximum time between two clicks on the same [`TreeItem`] to be considered a double click by [`handle_mouse`](Self::handle_mouse).
+    ///
+    /// Defaults to 500 ms.
+    pub const fn set_double_click_interval(&mut self, interval: Duration) {
+        self.mouse.double_click_interval = interval;
+    }
+
+    /// Handles a crossterm [`MouseEvent`] based on what was rendered on last render.
+    ///
+    /// - Scrolling the wheel scrolls the view (see [`set_mouse_scroll_lines`](Self::set_mouse_scroll_lines))
+    /// - A left click selects the clicked [`TreeItem`]
+    /// - A double click toggles a branch or activates a leaf (see [`set_double_click_interval`](Self::set_double_click_interval))
+    /// - A left click on the show more row of a [paginated](TreeItem::paginated) branch shows the next page
+    ///
+    /// Events outside of the last rendered area are [`MouseOutcome::Ignored`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    /// # use tui_tree_widget::{MouseOutcome, TreeItem, TreeState};
+    /// # let items = vec![TreeItem::new_leaf("a", "Alfa")];
+    /// # let mut state = TreeState::default();
+    /// # let event = MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: 0, row: 0, modifiers: KeyModifiers::NONE };
+    /// match state.handle_mouse(event, &items) {
+    ///     MouseOutcome::Activated(identifier) => println!("open {identifier:?}"),
+    ///     MouseOutcome::Ignored => {}
+    ///     _ => println!("redraw"),
+    /// }
+    /// ```
+    pub fn handle_mouse(
+        &mut self,
+        event: MouseEvent,
+        items: &[TreeItem<Identifier>],
+    ) -> MouseOutcome<Identifier> {
+        self.handle_mouse_at(event, items, Instant::now())
+    }
+
+    /// Same as [`handle_mouse`](Self::handle_mouse) but with a given point in time of the event.
+    ///
+    /// This is helpful when the time of the event is known more precisely or for testing.
+    pub fn handle_mouse_at(
+        &mut self,
+        event: MouseEvent,
+        items: &[TreeItem<Identifier>],
+        now: Instant,
+    ) -> MouseOutcome<Identifier> {
+        let position = Position::new(event.column, event.row);
+        if !self.last_area.contains(position) {
+            return MouseOutcome::Ignored;
+        }
+
+        let changed = match event.kind {
+            MouseEventKind::ScrollDown => self.scroll_down(self.mouse.scroll_lines),
+            MouseEventKind::ScrollUp => self.scroll_up(self.mouse.scroll_lines),
+            MouseEventKind::Down(MouseButton::Left) => {
+                return self.left_click(position, items, now)
+            }
+            _ => false,
+        };
+        if changed {
+            MouseOutcome::Scrolled
+        } else {
+            MouseOutcome::Ignored
+        }
+    }
+
+    fn left_click(
+        &mut self,
+        position: Position,
+        items: &[TreeItem<Identifier>],
+        now: Instant,
+    ) -> MouseOutcome<Identifier> {
+        let Some(identifier) = self.rendered_at(position).map(<[Identifier]>::to_vec) else {
+            self.mouse.last_click = None;
+            return MouseOutcome::Ignored;
+        };
+
+        if TreeItem::find_show_more_parent(items, &identifier).is_some() {
+            self.mouse.last_click = None;
+            self.select(identifier);
+            _ = self.enter(items);
+            return MouseOutcome::NextPage;
+        }
+
+        let is_double_click = self.mouse.last_click.take().is_some_and(|(time, last)| {
+            last == identifier
+                && now.saturating_duration_since(time) <= self.mouse.double_click_interval
+        });
+        if !is_double_click {
+            self.mouse.last_click = Some((now, identifier.clone()));
+            return if self.select(identifier) {
+                MouseOutcome::Selected
+            } else {
+                MouseOutcome::Ignored
+            };
+        }
+
+        let is_leaf = TreeItem::find(items, &identifier)
+            .is_none_or(|item| item.children.is_empty() && !item.lazy);
+        if is_leaf {
+            MouseOutcome::Activated(identifier)
+        } else {
+            self.ensure_selected_in_view_on_next_render = true;
+            self.toggle(identifier);
+            MouseOutcome::Toggled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidget;
+
+    use super::*;
+    use crate::Tree;
+
+    fn event(kind: MouseEventKind, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: 2,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn click(row: u16) -> MouseEvent {
+        event(MouseEventKind::Down(MouseButton::Left), row)
+    }
+
+    fn render(items: &[TreeItem<'static, &'static str>], state: &mut TreeState<&'static str>) {
+        let area = Rect::new(0, 0, 10, 4);
+        let tree = Tree::new(items).unwrap();
+        StatefulWidget::render(tree, area, &mut Buffer::empty(area), state);
+    }
+
+    #[test]
+    fn click_on_show_more_shows_next_page() {
+        let children = vec![TreeItem::new_leaf("c", "C"), TreeItem::new_leaf("d", "D")];
+        let items = vec![TreeItem::new("b", "B", children)
+            .unwrap()
+            .paginated(1, "more")
+            .unwrap()];
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        render(&items, &mut state);
+        let outcome = state.handle_mouse_at(click(2), &items, Instant::now());
+        assert_eq!(outcome, MouseOutcome::NextPage);
+        assert_eq!(state.selected(), ["b", "d"]);
+    }
+
+    #[test]
+    fn click_selects() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        assert_eq!(
+            state.handle_mouse_at(click(1), &items, now),
+            MouseOutcome::Selected
+        );
+        assert_eq!(state.selected(), ["b"]);
+        assert!(state.opened().is_empty());
+    }
+
+    #[test]
+    fn double_click_toggles_branch() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        _ = state.handle_mouse_at(click(1), &items, now);
+        let outcome = state.handle_mouse_at(click(1), &items, now + Duration::from_millis(100));
+        assert_eq!(outcome, MouseOutcome::Toggled);
+        assert!(state.opened().contains(&["b"]));
+    }
+
+    #[test]
+    fn double_click_activates_leaf() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        _ = state.handle_mouse_at(click(2), &items, now);
+        let outcome = state.handle_mouse_at(click(2), &items, now + Duration::from_millis(100));
+        assert_eq!(outcome, MouseOutcome::Activated(vec!["h"]));
+    }
+
+    #[test]
+    fn slow_clicks_are_no_double_click() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.set_double_click_interval(Duration::from_millis(200));
+        render(&items, &mut state);
+        let now = Instant::now();
+        _ = state.handle_mouse_at(click(1), &items, now);
+        let outcome = state.handle_mouse_at(click(1), &items, now + Duration::from_millis(300));
+        assert_eq!(outcome, MouseOutcome::Ignored);
+        assert!(state.opened().is_empty());
+    }
+
+    #[test]
+    fn clicks_on_different_rows_are_no_double_click() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        _ = state.handle_mouse_at(click(0), &items, now);
+        let outcome = state.handle_mouse_at(click(2), &items, now + Duration::from_millis(100));
+        assert_eq!(outcome, MouseOutcome::Selected);
+        assert_eq!(state.selected(), ["h"]);
+    }
+
+    #[test]
+    fn outside_is_ignored() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        render(&items, &mut state);
+        let now = Instant::now();
+        assert_eq!(
+            state.handle_mouse_at(click(10), &items, now),
+            MouseOutcome::Ignored
+        );
+        assert!(state.selected().is_empty());
+    }
+
+    #[test]
+    fn wheel_scrolls() {
+        let items = TreeItem::example();
+        let mut state = TreeState::default();
+        state.open(vec!["b"]);
+        state.set_mouse_scroll_lines(2);
+        render(&items, &mut state);
+        let now = Instant::now();
+        assert_eq!(
+            state.handle_mouse_at(event(MouseEventKind::ScrollDown, 0), &items, now),
+            MouseOutcome::Scrolled
+        );
+        assert_eq!(state.get_offset(), 2);
+        assert_eq!(
+            state.handle_mouse_at(event(MouseEventKind::ScrollUp, 0), &items, now),
+            MouseOutcome::Scrolled
+        );
+        assert_eq!(state.get_offset(), 0);
+        assert_eq!(
+            state.handle_mouse_at(event(MouseEventKind::ScrollUp, 0), &items, now),
+            MouseOutcome::Ignored
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::tree_item::TreeItem;
+use crate::tree_state::TreeState;
+
+/// Importance of a [`TreeItem`] shown in an [`overview`].
+///
+/// When multiple items are summarized by one [`OverviewBucket`] the most important one is used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OverviewClass {
+    #[default]
+    Normal,
+    Match,
+    Warning,
+    Error,
+}
+
+/// A segment of all currently viewable [`TreeItem`]s created by [`overview`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OverviewBucket {
+    /// Most important class of the items in this segment
+    pub class: OverviewClass,
+    /// At least one of the items in this segment was rendered on last render
+    pub in_view: bool,
+}
+
+/// Summarize all currently viewable (including by scrolling) [`TreeItem`]s in `buckets` segments of equal size.
+///
+/// This can be used to render an overview next to the [`Tree`](crate::Tree) like the minimap of an editor.
+/// Each item is classified by the given function with its identifier including the identifiers of its parents.
+/// The part in view is based on the last render.
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::{overview, OverviewClass, TreeItem, TreeState};
+/// # let items = vec![TreeItem::new_leaf("error", "Error")];
+/// # let state = TreeState::default();
+/// let buckets = overview(&items, &state, 10, |identifier, _item| {
+///     if identifier.last() == Some(&"error") {
+///         OverviewClass::Error
+///     } else {
+///         OverviewClass::Normal
+///     }
+/// });
+/// assert_eq!(buckets.len(), 10);
+/// ```
+#[must_use]
+pub fn overview<Identifier, F>(
+    items: &[TreeItem<Identifier>],
+    state: &TreeState<Identifier>,
+    buckets: usize,
+    classify: F,
+) -> Vec<OverviewBucket>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+    F: Fn(&[Identifier], &TreeItem<Identifier>) -> OverviewClass,
+{
+    let visible = state.flatten(items);
+    let rows = visible.len();
+    if buckets == 0 {
+        return Vec::new();
+    }
+    let view_start = state.offset;
+    let view_end = view_start + state.last_rendered_identifiers.len();
+
+    let mut result = vec![OverviewBucket::default(); buckets];
+    for (index, flattened) in visible.iter().enumerate() {
+        let bucket = &mut result[index * buckets / rows];
+        bucket.class = bucket
+            .class
+            .max(classify(&flattened.identifier, flattened.item));
+        bucket.in_view |= (view_start..view_end).contains(&index);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidget;
+
+    use super::*;
+    use crate::Tree;
+
+    fn classify(identifier: &[usize], _item: &TreeItem<usize>) -> OverviewClass {
+        if identifier == [55] {
+            OverviewClass::Error
+        } else {
+            OverviewClass::Normal
+        }
+    }
+
+    #[test]
+    fn buckets_with_viewport() {
+        let items = (0..100)
+            .map(|index| TreeItem::new_leaf(index, index.to_string()))
+            .collect::<Vec<_>>();
+        let mut state = TreeState::default();
+        let area = Rect::new(0, 0, 10, 20);
+        let render = |state: &mut TreeState<usize>| {
+            StatefulWidget::render(
+                Tree::new(&items).unwrap(),
+                area,
+                &mut Buffer::empty(area),
+                state,
+            );
+        };
+        render(&mut state);
+        assert!(state.scroll_down(20));
+        render(&mut state);
+
+        let buckets = overview(&items, &state, 10, classify);
+        let classes = buckets
+            .iter()
+            .map(|bucket| bucket.class)
+            .collect::<Vec<_>>();
+        let mut expected = [OverviewClass::Normal; 10];
+        expected[5] = OverviewClass::Error;
+        assert_eq!(classes, expected);
+        let in_view = buckets
+            .iter()
+            .map(|bucket| bucket.in_view)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            in_view,
+            [false, false, true, true, false, false, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn more_buckets_than_rows() {
+        let items = vec![TreeItem::new_leaf(55, "55"), TreeItem::new_leaf(1, "1")];
+        let state = TreeState::default();
+        let buckets = overview(&items, &state, 4, classify);
+        assert_eq!(buckets[0].class, OverviewClass::Error);
+        assert_eq!(buckets[1], OverviewBucket::default());
+        assert_eq!(buckets[2].class, OverviewClass::Normal);
+        assert!(overview(&items, &state, 0, classify).is_empty());
+    }
+}
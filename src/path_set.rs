@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+/// Set of identifiers like the [opened](crate::TreeState::opened) ones.
+///
+/// Identifiers starting with the same parents share them: each node only keeps its own identifier and its parent.
+/// This way the memory scales with the amount of different nodes instead of the length of all identifiers together.
+#[derive(Clone)]
+pub struct PathSet<Identifier> {
+    /// Unused slots are `None` and listed in `free`
+    nodes: Vec<Option<Node<Identifier>>>,
+    free: Vec<usize>,
+    /// Top level nodes by their identifier
+    roots: HashMap<Identifier, usize>,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Node<Identifier> {
+    identifier: Identifier,
+    parent: Option<usize>,
+    children: HashMap<Identifier, usize>,
+    /// The identifier ending with this node is part of the set, otherwise it is only the parent of one
+    contained: bool,
+}
+
+impl<Identifier> Default for PathSet<Identifier> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            roots: HashMap::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<Identifier: core::fmt::Debug> core::fmt::Debug for PathSet<Identifier> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let identifiers = self
+            .nodes
+            .iter()
+            .flatten()
+            .filter(|node| node.contained)
+            .map(|node| {
+                let mut identifier = vec![&node.identifier];
+                let mut parent = node.parent;
+                while let Some(node) = parent.and_then(|index| self.nodes[index].as_ref()) {
+                    identifier.push(&node.identifier);
+                    parent = node.parent;
+                }
+                identifier.reverse();
+                identifier
+            });
+        f.debug_set().entries(identifiers).finish()
+    }
+}
+
+impl<Identifier> PartialEq for PathSet<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|identifier| other.contains(&identifier))
+    }
+}
+
+impl<Identifier> Eq for PathSet<Identifier> where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash
+{
+}
+
+impl<Identifier> PathSet<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Amount of identifiers in the set.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn contains(&self, identifier: &[Identifier]) -> bool {
+        self.find(identifier)
+            .is_some_and(|index| self.node(index).contained)
+    }
+
+    /// All identifiers of the set in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<Identifier>> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.as_ref().is_some_and(|node| node.contained))
+            .map(|(index, _)| self.identifier(index))
+    }
+
+    /// Node of the identifier, even when it is only the parent of one in the set.
+    pub(super) fn find(&self, identifier: &[Identifier]) -> Option<usize> {
+        let mut index = None;
+        for identifier in identifier {
+            index = Some(self.child(index, identifier)?);
+        }
+        index
+    }
+
+    /// Identifier ending with the node.
+    pub(super) fn identifier(&self, mut index: usize) -> Vec<Identifier> {
+        let mut identifier = Vec::new();
+        loop {
+            let node = self.node(index);
+            identifier.push(node.identifier.clone());
+            let Some(parent) = node.parent else {
+                break;
+            };
+            index = parent;
+        }
+        identifier.reverse();
+        identifier
+    }
+
+    /// Node of the child with the given identifier below the node `parent` or at the top level.
+    pub(super) fn child(&self, parent: Option<usize>, identifier: &Identifier) -> Option<usize> {
+        let siblings = parent.map_or(&self.roots, |parent| &self.node(parent).children);
+        siblings.get(identifier).copied()
+    }
+
+    /// Whether the identifier ending with the node is part of the set.
+    pub(super) fn contains_node(&self, index: usize) -> bool {
+        self.node(index).contained
+    }
+
+    /// Add the identifier.
+    ///
+    /// Returns `true` when it was not part of the set before.
+    /// Empty identifiers are never part of the set.
+    pub(super) fn insert(&mut self, identifier: &[Identifier]) -> bool {
+        let mut parent = None;
+        for identifier in identifier {
+            parent = Some(
+                self.child(parent, identifier)
+                    .unwrap_or_else(|| self.add_node(parent, identifier.clone())),
+            );
+        }
+        let Some(index) = parent else {
+            return false;
+        };
+        let node = self.node_mut(index);
+        if node.contained {
+            return false;
+        }
+        node.contained = true;
+        self.len += 1;
+        true
+    }
+
+    /// Add the identifier made of the node `parent` and the given child.
+    ///
+    /// Returns the node of the child and `true` when it was not part of the set before.
+    pub(super) fn insert_child(&mut self, parent: usize, identifier: &Identifier) -> (usize, bool) {
+        let index = self
+            .child(Some(parent), identifier)
+            .unwrap_or_else(|| self.add_node(Some(parent), identifier.clone()));
+        let node = self.node_mut(index);
+        if node.contained {
+            return (index, false);
+        }
+        node.contained = true;
+        self.len += 1;
+        (index, true)
+    }
+
+    /// Returns `true` when the identifier was part of the set.
+    pub(super) fn remove(&mut self, identifier: &[Identifier]) -> bool {
+        let Some(index) = self.find(identifier) else {
+            return false;
+        };
+        let node = self.node_mut(index);
+        if !node.contained {
+            return false;
+        }
+        node.contained = false;
+        self.len -= 1;
+        self.remove_unused(index);
+        true
+    }
+
+    /// Only keep the identifiers for which `keep` returns `true`.
+    pub(super) fn retain(&mut self, mut keep: impl FnMut(&[Identifier]) -> bool) {
+        // Walk the nodes with an explicit stack, `identifier` is the path of the current one
+        let mut identifier = Vec::new();
+        let mut stack = self
+            .roots
+            .values()
+            .map(|index| (*index, 0))
+            .collect::<Vec<_>>();
+        let mut removed = Vec::new();
+        while let Some((index, depth)) = stack.pop() {
+            let node = self.node(index);
+            identifier.truncate(depth);
+            identifier.push(node.identifier.clone());
+            if node.contained && !keep(&identifier) {
+                removed.push(index);
+            }
+            stack.extend(node.children.values().map(|child| (*child, depth + 1)));
+        }
+
+        for index in &removed {
+            self.node_mut(*index).contained = false;
+        }
+        self.len -= removed.len();
+        for index in removed {
+            self.remove_unused(index);
+        }
+    }
+
+    /// Amount of nodes stored, which is the amount of different identifiers and their parents.
+    #[cfg(test)]
+    pub(super) const fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn node(&self, index: usize) -> &Node<Identifier> {
+        self.nodes[index].as_ref().expect("index of a used node")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<Identifier> {
+        self.nodes[index].as_mut().expect("index of a used node")
+    }
+
+    fn add_node(&mut self, parent: Option<usize>, identifier: Identifier) -> usize {
+        let node = Node {
+            identifier: identifier.clone(),
+            parent,
+            children: HashMap::new(),
+            contained: false,
+        };
+        let index = if let Some(index) = self.free.pop() {
+            self.nodes[index] = Some(node);
+            index
+        } else {
+            self.nodes.push(Some(node));
+            self.nodes.len() - 1
+        };
+        match parent {
+            Some(parent) => self.node_mut(parent).children.insert(identifier, index),
+            None => self.roots.insert(identifier, index),
+        };
+        index
+    }
+
+    /// Remove the node and its parents as long as they are neither part of the set nor a parent of something else.
+    fn remove_unused(&mut self, mut index: usize) {
+        loop {
+            let unused = self.nodes[index]
+                .as_ref()
+                .is_some_and(|node| !node.contained && node.children.is_empty());
+            if !unused {
+                return;
+            }
+            let node = self.nodes[index].take().expect("index of a used node");
+            self.free.push(index);
+            let Some(parent) = node.parent else {
+                self.roots.remove(&node.identifier);
+                return;
+            };
+            self.node_mut(parent).children.remove(&node.identifier);
+            index = parent;
+        }
+    }
+}
+
+#[test]
+fn shares_parents() {
+    let mut set = PathSet::default();
+    assert!(set.insert(&["a", "b", "c"]));
+    assert!(set.insert(&["a", "b"]));
+    assert!(set.insert(&["a", "d"]));
+    assert!(!set.insert(&["a", "b"]));
+    assert!(!set.insert(&[]));
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.node_count(), 4);
+    assert!(set.contains(&["a", "b", "c"]));
+    assert!(!set.contains(&["a"]));
+    assert!(!set.contains(&["a", "b", "c", "d"]));
+
+    let mut all = set.iter().collect::<Vec<_>>();
+    all.sort();
+    assert_eq!(all, [vec!["a", "b"], vec!["a", "b", "c"], vec!["a", "d"]]);
+
+    let parent = set.find(&["a", "b"]).unwrap();
+    assert_eq!(set.identifier(parent), ["a", "b"]);
+    assert!(!set.insert_child(parent, &"c").1);
+    let (child, inserted) = set.insert_child(parent, &"e");
+    assert!(inserted);
+    assert_eq!(set.identifier(child), ["a", "b", "e"]);
+    assert!(set.remove(&["a", "b", "e"]));
+
+    assert!(set.remove(&["a", "b", "c"]));
+    assert!(!set.remove(&["a", "b", "c"]));
+    assert!(!set.remove(&["a"]));
+    assert_eq!(set.node_count(), 3);
+
+    set.retain(|identifier| identifier.len() < 2 || identifier[1] != "d");
+    assert_eq!(set.iter().collect::<Vec<_>>(), [vec!["a", "b"]]);
+    assert_eq!(set.node_count(), 2);
+    assert!(set.remove(&["a", "b"]));
+    assert!(set.is_empty());
+    assert_eq!(set.node_count(), 0);
+}
@@ -0,0 +1,209 @@
+use crate::tree_item::TreeItem;
+use crate::tree_state::TreeState;
+
+/// A change of the [`TreeState`] applied with [`TreeState::apply`].
+///
+/// Keeping these around can be used as a log of changes, for example to restore a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateOp<Identifier> {
+    /// See [`TreeState::open`]
+    Open(Vec<Identifier>),
+    /// See [`TreeState::close`]
+    Close(Vec<Identifier>),
+    /// See [`TreeState::toggle`]
+    Toggle(Vec<Identifier>),
+    /// See [`TreeState::close_all`]
+    CloseAll,
+    /// See [`TreeState::select`]
+    Select(Vec<Identifier>),
+    /// Scroll to the given offset of the first shown item
+    ScrollTo(usize),
+}
+
+impl<Identifier> StateOp<Identifier> {
+    /// Identifier the operation refers to, if any.
+    #[must_use]
+    pub fn identifier(&self) -> Option<&[Identifier]> {
+        match self {
+            Self::Open(identifier)
+            | Self::Close(identifier)
+            | Self::Toggle(identifier)
+            | Self::Select(identifier) => Some(identifier),
+            Self::CloseAll | Self::ScrollTo(_) => None,
+        }
+    }
+}
+
+/// Result of [`TreeState::apply`].
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyReport<Identifier> {
+    /// Operations referring to identifiers which do not exist in the items, in the order they were given
+    pub missing: Vec<StateOp<Identifier>>,
+    /// Whether the state changed
+    ///
+    /// This includes the state of items which do not exist (anymore) removed afterwards, even when no operation referred to them.
+    pub changed: bool,
+}
+
+impl<Identifier> TreeState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Apply multiple [`StateOp`]s at once.
+    ///
+    /// All operations are applied in order.
+    /// Afterwards the state of items which do not exist is removed once, see [`prune`](Self::prune).
+    /// This covers the whole state, not only the state the operations referred to.
+    /// Operations referring to items which do not exist are listed in the [`ApplyReport`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{StateOp, TreeItem, TreeState};
+    /// let items = vec![TreeItem::new("b", "Bravo", vec![TreeItem::new_leaf("c", "Charlie")])?];
+    /// let mut state = TreeState::default();
+    /// let report = state.apply(&items, [
+    ///     StateOp::Open(vec!["b"]),
+    ///     StateOp::Open(vec!["gone"]),
+    ///     StateOp::Select(vec!["b", "c"]),
+    /// ]);
+    /// assert_eq!(report.missing, [StateOp::Open(vec!["gone"])]);
+    /// assert_eq!(state.selected(), ["b", "c"]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn apply<I>(&mut self, items: &[TreeItem<Identifier>], ops: I) -> ApplyReport<Identifier>
+    where
+        I: IntoIterator<Item = StateOp<Identifier>>,
+    {
+        self.undoable(|state| state.apply_inner(items, ops))
+    }
+
+    fn apply_inner<I>(&mut self, items: &[TreeItem<Identifier>], ops: I) -> ApplyReport<Identifier>
+    where
+        I: IntoIterator<Item = StateOp<Identifier>>,
+    {
+        let ops = ops.into_iter().collect::<Vec<_>>();
+        let mut changed = false;
+        let mut ensure_selected_in_view = self.ensure_selected_in_view_on_next_render;
+        for op in &ops {
+            changed |= match op {
+                StateOp::Open(identifier) => self.open(identifier.clone()),
+                StateOp::Close(identifier) => self.close(identifier),
+                StateOp::Toggle(identifier) => self.toggle(identifier.clone()),
+                StateOp::CloseAll => self.close_all(),
+                StateOp::Select(identifier) => {
+                    ensure_selected_in_view = true;
+                    self.select(identifier.clone())
+                }
+                StateOp::ScrollTo(offset) => {
+                    ensure_selected_in_view = false;
+                    let changed = self.offset != *offset;
+                    self.offset = *offset;
+                    changed
+                }
+            };
+        }
+
+        let identifiers = ops
+            .iter()
+            .filter_map(StateOp::identifier)
+            .filter(|identifier| !identifier.is_empty())
+            .collect::<Vec<_>>();
+        let existing = TreeItem::existing(items, &identifiers);
+        let is_missing = ops
+            .iter()
+            .map(|op| {
+                op.identifier().is_some_and(|identifier| {
+                    !identifier.is_empty() && !existing.contains(identifier)
+                })
+            })
+            .collect::<Vec<_>>();
+        let missing = ops
+            .into_iter()
+            .zip(is_missing)
+            .filter_map(|(op, is_missing)| is_missing.then_some(op))
+            .collect();
+        changed |= self.prune(items);
+        self.ensure_selected_in_view_on_next_render = ensure_selected_in_view;
+        ApplyReport { missing, changed }
+    }
+}
+
+#[test]
+fn apply_mixed_batch() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["h"]);
+    let report = state.apply(
+        &items,
+        [
+            StateOp::CloseAll,
+            StateOp::Open(vec!["b"]),
+            StateOp::Open(vec!["b", "d"]),
+            StateOp::Open(vec!["b", "x"]),
+            StateOp::Toggle(vec!["b", "d"]),
+            StateOp::Toggle(vec!["b", "d"]),
+            StateOp::Select(vec!["b", "x"]),
+            StateOp::ScrollTo(2),
+        ],
+    );
+    assert_eq!(
+        report.missing,
+        [
+            StateOp::Open(vec!["b", "x"]),
+            StateOp::Select(vec!["b", "x"])
+        ]
+    );
+    assert!(report.changed);
+    assert_eq!(state.opened().len(), 2);
+    assert!(state.opened().contains(&["b"]));
+    assert!(state.opened().contains(&["b", "d"]));
+    assert_eq!(state.selected(), ["b"]);
+    assert_eq!(state.get_offset(), 2);
+
+    let report = state.apply(&items, [StateOp::Open(vec!["b"])]);
+    assert!(report.missing.is_empty());
+    assert!(!report.changed);
+}
+
+#[test]
+fn apply_prunes_unrelated_state() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["gone"]);
+    let report = state.apply(&items, [StateOp::Select(vec!["b", "c"])]);
+    assert!(report.missing.is_empty());
+    assert!(report.changed);
+    assert!(state.opened().is_empty());
+
+    state.open(vec!["gone"]);
+    let report = state.apply(&items, [StateOp::Select(vec!["b", "c"])]);
+    assert!(report.changed);
+    let report = state.apply(&items, [StateOp::Select(vec!["b", "c"])]);
+    assert!(!report.changed);
+}
+
+#[test]
+fn apply_finds_show_more_rows() {
+    let children = (0..5).map(|index| TreeItem::new_leaf(index, "")).collect();
+    let items = vec![TreeItem::new(10, "", children)
+        .unwrap()
+        .paginated(2, 11)
+        .unwrap()];
+    let mut state = TreeState::default();
+    let report = state.apply(
+        &items,
+        [
+            StateOp::Open(vec![10]),
+            StateOp::Select(vec![10, 11]),
+            StateOp::Select(vec![10, 11, 0]),
+            StateOp::Select(vec![10, 4]),
+            StateOp::Select(vec![11]),
+        ],
+    );
+    assert_eq!(
+        report.missing,
+        [StateOp::Select(vec![10, 11, 0]), StateOp::Select(vec![11])]
+    );
+}
@@ -0,0 +1,335 @@
+use ratatui::layout::Rect;
+use ratatui::Frame;
+
+use crate::event::{TreeEffect, TreeEvent};
+use crate::state_op::{ApplyReport, StateOp};
+use crate::tree_item::TreeItem;
+use crate::tree_state::{Enter, ToggleOutcome, TooDeep, TreeState};
+use crate::Tree;
+
+/// Owns the [`TreeItem`]s together with their [`TreeState`].
+///
+/// Operations needing both can not be called with items and a state which do not belong together.
+/// Changing the items with [`set_items`](Self::set_items) or [`update_items`](Self::update_items) forgets the state of removed items.
+/// Both fields are public so everything not forwarded by this type can still be used.
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::{TreeData, TreeItem};
+/// let mut data = TreeData::new(vec![TreeItem::new("r", "Root", vec![TreeItem::new_leaf("l", "Leaf")])?]);
+/// data.open_all();
+/// data.state.select(vec!["r", "l"]);
+/// assert_eq!(data.selected_item().map(|item| item.identifier()), Some(&"l"));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeData<'text, Identifier> {
+    pub items: Vec<TreeItem<'text, Identifier>>,
+    pub state: TreeState<Identifier>,
+}
+
+impl<'text, Identifier> TreeData<'text, Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Create with the given items and a [default](TreeState::default) state.
+    #[must_use]
+    pub fn new(items: Vec<TreeItem<'text, Identifier>>) -> Self
+    where
+        Identifier: Default,
+    {
+        Self {
+            items,
+            state: TreeState::default(),
+        }
+    }
+
+    /// The [`TreeItem`] of the given identifier including the identifiers of its parents.
+    #[must_use]
+    pub fn find(&self, identifier: &[Identifier]) -> Option<&TreeItem<'text, Identifier>> {
+        TreeItem::find(&self.items, identifier)
+    }
+
+    /// The currently selected [`TreeItem`].
+    #[must_use]
+    pub fn selected_item(&self) -> Option<&TreeItem<'text, Identifier>> {
+        self.find(self.state.selected())
+    }
+
+    /// See [`TreeState::selected`].
+    #[must_use]
+    pub fn selected(&self) -> &[Identifier] {
+        self.state.selected()
+    }
+
+    /// Replace the items and forget the state of items which no longer exist.
+    /// See [`TreeState::prune`].
+    pub fn set_items(&mut self, items: Vec<TreeItem<'text, Identifier>>) {
+        self.items = items;
+        self.state.prune(&self.items);
+    }
+
+    /// Change the items and forget the state of items which no longer exist afterwards.
+    /// See [`TreeState::prune`].
+    pub fn update_items<F, R>(&mut self, update: F) -> R
+    where
+        F: FnOnce(&mut Vec<TreeItem<'text, Identifier>>) -> R,
+    {
+        let result = update(&mut self.items);
+        self.state.prune(&self.items);
+        result
+    }
+
+    /// Open all [`TreeItem`]s with children.
+    ///
+    /// Returns the amount of items which were closed and have been opened.
+    pub fn open_all(&mut self) -> usize {
+        let items = &self.items;
+        self.state.undoable(|state| {
+            let mut opened = 0;
+            for item in items.iter().filter(|item| !item.children.is_empty()) {
+                opened +=
+                    state.open_recursive(items, std::slice::from_ref(&item.identifier), usize::MAX);
+            }
+            opened
+        })
+    }
+
+    /// See [`TreeState::enter`].
+    pub fn enter(&mut self) -> Enter<Identifier> {
+        self.state.enter(&self.items)
+    }
+
+    /// See [`TreeState::toggle_item`].
+    pub fn toggle_item(&mut self, identifier: Vec<Identifier>) -> ToggleOutcome<Identifier> {
+        self.state.toggle_item(&self.items, identifier)
+    }
+
+    /// See [`TreeState::toggle_selected_item`].
+    pub fn toggle_selected_item(&mut self) -> ToggleOutcome<Identifier> {
+        self.state.toggle_selected_item(&self.items)
+    }
+
+    /// See [`TreeState::open_recursive`].
+    pub fn open_recursive(&mut self, identifier: &[Identifier], depth: usize) -> usize {
+        self.state.open_recursive(&self.items, identifier, depth)
+    }
+
+    /// See [`TreeState::open_siblings`].
+    pub fn open_siblings(&mut self) -> usize {
+        self.state.open_siblings(&self.items)
+    }
+
+    /// See [`TreeState::close_siblings`].
+    pub fn close_siblings(&mut self) -> usize {
+        self.state.close_siblings(&self.items)
+    }
+
+    /// See [`TreeState::close_all`].
+    pub fn close_all(&mut self) -> bool {
+        self.state.close_all()
+    }
+
+    /// See [`TreeState::prune`].
+    ///
+    /// Only needed when the [`items`](Self::items) were changed directly instead of with [`set_items`](Self::set_items) or [`update_items`](Self::update_items).
+    pub fn prune(&mut self) -> bool {
+        self.state.prune(&self.items)
+    }
+
+    /// See [`TreeState::apply`].
+    pub fn apply<I>(&mut self, ops: I) -> ApplyReport<Identifier>
+    where
+        I: IntoIterator<Item = StateOp<Identifier>>,
+    {
+        self.state.apply(&self.items, ops)
+    }
+
+    /// See [`TreeState::push_event`].
+    pub fn push_event(&mut self, event: TreeEvent<Identifier>) {
+        self.state.push_event(event);
+    }
+
+    /// See [`TreeState::drain_events`].
+    pub fn drain_events(&mut self) -> Vec<TreeEffect<Identifier>> {
+        self.state.drain_events(&self.items)
+    }
+
+    /// See [`TreeState::select`].
+    pub fn select(&mut self, identifier: Vec<Identifier>) -> bool {
+        self.state.select(identifier)
+    }
+
+    /// See [`TreeState::try_select`].
+    ///
+    /// # Errors
+    ///
+    /// Errors without selecting when the identifier is longer than the [max path depth](TreeState::set_max_path_depth).
+    pub fn try_select(&mut self, identifier: Vec<Identifier>) -> Result<bool, TooDeep> {
+        self.state.try_select(identifier)
+    }
+
+    /// See [`TreeState::select_first`].
+    pub fn select_first(&mut self) -> bool {
+        self.state.select_first()
+    }
+
+    /// See [`TreeState::select_last`].
+    pub fn select_last(&mut self) -> bool {
+        self.state.select_last()
+    }
+
+    /// See [`TreeState::key_up`].
+    pub fn key_up(&mut self) -> bool {
+        self.state.key_up()
+    }
+
+    /// See [`TreeState::key_down`].
+    pub fn key_down(&mut self) -> bool {
+        self.state.key_down()
+    }
+
+    /// See [`TreeState::key_left`].
+    pub fn key_left(&mut self) -> bool {
+        self.state.key_left()
+    }
+
+    /// See [`TreeState::key_right`].
+    pub fn key_right(&mut self) -> bool {
+        self.state.key_right()
+    }
+
+    /// See [`TreeState::key_page_up`].
+    pub fn key_page_up(&mut self) -> bool {
+        self.state.key_page_up()
+    }
+
+    /// See [`TreeState::key_page_down`].
+    pub fn key_page_down(&mut self) -> bool {
+        self.state.key_page_down()
+    }
+
+    /// See [`TreeState::select_first_sibling`].
+    pub fn select_first_sibling(&mut self) -> bool {
+        self.state.select_first_sibling(&self.items)
+    }
+
+    /// See [`TreeState::select_last_sibling`].
+    pub fn select_last_sibling(&mut self) -> bool {
+        self.state.select_last_sibling(&self.items)
+    }
+
+    /// See [`TreeState::select_matching_end`].
+    pub fn select_matching_end(&mut self) -> bool {
+        self.state.select_matching_end(&self.items)
+    }
+
+    /// Total amount of [`TreeItem`]s including closed ones.
+    /// See [`TreeState::total_count`].
+    #[must_use]
+    pub fn total_count(&self) -> usize {
+        TreeState::total_count(&self.items)
+    }
+
+    /// See [`TreeState::visible_count`].
+    #[must_use]
+    pub const fn visible_count(&self) -> usize {
+        self.state.visible_count()
+    }
+
+    /// See [`TreeState::render_signature`].
+    #[must_use]
+    pub fn render_signature(&self) -> u64 {
+        self.state.render_signature(&self.items)
+    }
+
+    /// Create a [`Tree`] widget of the items.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are duplicate identifiers in the top level items.
+    pub fn widget(&self) -> std::io::Result<Tree<'_, Identifier>> {
+        Tree::new(&self.items)
+    }
+
+    /// Render a [`Tree`] with default styling of the items into the given area.
+    ///
+    /// Use [`widget`](Self::widget) and [`Frame::render_stateful_widget`] with the [`state`](Self::state) for custom styling.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are duplicate identifiers in the top level items.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) -> std::io::Result<()> {
+        let tree = Tree::new(&self.items)?;
+        frame.render_stateful_widget(tree, area, &mut self.state);
+        Ok(())
+    }
+}
+
+#[test]
+fn interaction_flow() {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    let mut terminal = Terminal::new(TestBackend::new(20, 6)).unwrap();
+    let mut data = TreeData::new(TreeItem::example());
+    assert_eq!(data.total_count(), 8);
+    let mut draw = |data: &mut TreeData<&'static str>| {
+        terminal
+            .draw(|frame| data.render(frame, frame.size()).unwrap())
+            .unwrap();
+    };
+
+    draw(&mut data);
+    assert!(data.key_down());
+    assert!(data.key_down());
+    assert_eq!(data.enter(), Enter::Toggled(true));
+    draw(&mut data);
+    assert!(data.key_down());
+    assert!(data.key_down());
+    assert!(data.key_right());
+    draw(&mut data);
+    assert_eq!(data.visible_count(), 8);
+    assert!(data.select_matching_end());
+    assert_eq!(data.selected_item().unwrap().identifier(), &"f");
+    assert!(data.key_left());
+    assert_eq!(data.selected(), ["b", "d"]);
+    assert_eq!(data.toggle_selected_item(), ToggleOutcome::Toggled(false));
+    assert_eq!(data.open_siblings(), 1);
+    assert_eq!(data.close_siblings(), 0);
+    draw(&mut data);
+    assert_eq!(data.visible_count(), 8);
+
+    let removed = data.update_items(|items| items[1].children.remove(1));
+    assert_eq!(removed.identifier(), &"d");
+    assert_eq!(data.selected(), ["b"]);
+    draw(&mut data);
+    assert_eq!(data.visible_count(), 5);
+
+    let report = data.apply([StateOp::Select(vec!["b", "d"])]);
+    assert_eq!(report.missing, [StateOp::Select(vec!["b", "d"])]);
+    data.push_event(TreeEvent::Down);
+    assert_eq!(
+        data.drain_events(),
+        [TreeEffect::SelectionChanged(vec!["b", "c"])]
+    );
+    assert!(data.close_all());
+    assert_eq!(data.open_all(), 1);
+    data.set_items(TreeItem::example());
+    assert_eq!(data.open_recursive(&["b"], 2), 1);
+    assert_eq!(
+        data.toggle_item(vec!["h"]),
+        ToggleOutcome::NoChildren(vec!["h"])
+    );
+    draw(&mut data);
+    assert_eq!(data.visible_count(), 8);
+    assert!(data.select_last());
+    assert!(data.key_page_up());
+    assert_eq!(data.selected(), ["b"]);
+    assert!(data.key_up());
+    assert!(!data.key_up());
+    assert!(data.key_page_down());
+    assert_eq!(data.selected(), ["b", "g"]);
+    assert!(!data.prune());
+}
@@ -1,6 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
-use ratatui::text::Text;
+use ratatui::text::{Line, Text};
+
+use crate::Callback;
 
 /// One item inside a [`Tree`](crate::Tree).
 ///
@@ -33,11 +36,75 @@ use ratatui::text::Text;
 /// let b = TreeItem::new("r", "Root", vec![a])?;
 /// # Ok::<(), std::io::Error>(())
 /// ```
-#[derive(Debug, Clone)]
+///
+/// # Deep trees
+///
+/// Creating, cloning and dropping work without recursion so trees nested deeper than the stack allows are fine.
+/// The [`Debug`] output is recursive and overflows the stack for such trees.
+#[derive(Debug)]
 pub struct TreeItem<'text, Identifier> {
     pub(super) identifier: Identifier,
     pub(super) text: Text<'text>,
     pub(super) children: Vec<Self>,
+    /// Children are not loaded yet, see [`TreeItem::new_lazy`]
+    pub(super) lazy: bool,
+    /// Amount of children shown per page, see [`TreeItem::paginated`]
+    pub(super) page_size: usize,
+    /// Row shown after the children when not all of them are shown
+    pub(super) show_more: Option<Box<Self>>,
+    /// Shown below the item when selected, see [`TreeItem::description`]
+    pub(super) description: Option<Line<'text>>,
+    /// Creates the text on render instead of `text`, see [`TreeItem::new_dynamic_leaf`]
+    pub(super) dynamic_text: Option<Callback<DynamicTextFn>>,
+    /// Is a branch even without children, see [`TreeItem::expandable`]
+    pub(super) expandable: bool,
+    /// Header row of one of multiple roots, see [`TreeItem::new_header`]
+    pub(super) header: bool,
+}
+
+type DynamicTextFn = dyn Fn() -> Line<'static>;
+
+/// Drop the children without recursion to not overflow the stack with very deep trees.
+impl<Identifier> Drop for TreeItem<'_, Identifier> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut item) = stack.pop() {
+            stack.append(&mut item.children);
+        }
+    }
+}
+
+/// Clone the children without recursion to not overflow the stack with very deep trees.
+impl<Identifier: Clone> Clone for TreeItem<'_, Identifier> {
+    fn clone(&self) -> Self {
+        let shallow = |item: &Self| Self {
+            identifier: item.identifier.clone(),
+            text: item.text.clone(),
+            children: Vec::with_capacity(item.children.len()),
+            lazy: item.lazy,
+            page_size: item.page_size,
+            show_more: item.show_more.clone(),
+            description: item.description.clone(),
+            dynamic_text: item.dynamic_text.clone(),
+            expandable: item.expandable,
+            header: item.header,
+        };
+
+        // Each source item together with its clone containing the children cloned so far
+        let mut stack = vec![(self, shallow(self))];
+        loop {
+            let (source, clone) = stack.last_mut().expect("stack contains at least the root");
+            if let Some(child) = source.children.get(clone.children.len()) {
+                stack.push((child, shallow(child)));
+                continue;
+            }
+            let (_, clone) = stack.pop().expect("stack contains at least the root");
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(clone),
+                None => return clone,
+            }
+        }
+    }
 }
 
 impl<'text, Identifier> TreeItem<'text, Identifier>
@@ -54,9 +121,64 @@ where
             identifier,
             text: text.into(),
             children: Vec::new(),
+            lazy: false,
+            page_size: 0,
+            show_more: None,
+            description: None,
+            dynamic_text: None,
+            expandable: false,
+            header: false,
         }
     }
 
+    /// Create a new `TreeItem` without children whose text is created on each render.
+    ///
+    /// This is helpful for texts changing all the time like an elapsed time.
+    /// The function is only called when the item is rendered so items outside of the view cost nothing.
+    ///
+    /// Dynamic items are always one line high.
+    /// Only the [`Tree`](crate::Tree) evaluates the function, other places like [`breadcrumb`](crate::breadcrumb) see an empty text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Instant;
+    /// # use tui_tree_widget::TreeItem;
+    /// let start = Instant::now();
+    /// let item = TreeItem::new_dynamic_leaf("elapsed", move || {
+    ///     format!("{:.1}s", start.elapsed().as_secs_f32()).into()
+    /// });
+    /// ```
+    #[must_use]
+    pub fn new_dynamic_leaf<F>(identifier: Identifier, text: F) -> Self
+    where
+        F: Fn() -> Line<'static> + 'static,
+    {
+        let mut item = Self::new_leaf(identifier, "");
+        item.dynamic_text = Some(Callback(Rc::new(text)));
+        item
+    }
+
+    /// Evaluate the text of a [dynamic](Self::new_dynamic_leaf) item
+    pub(super) fn dynamic_text(&self) -> Option<Text<'text>> {
+        let dynamic_text = self.dynamic_text.as_ref()?;
+        Some(Text::from((dynamic_text.0)()).patch_style(self.text.style))
+    }
+
+    /// Create a new `TreeItem` whose children are loaded later.
+    ///
+    /// It is shown as a closed branch.
+    /// Once opened it is listed in [`TreeState::pending_loads`](crate::TreeState::pending_loads) until its children are installed with [`set_children_at`](Self::set_children_at).
+    #[must_use]
+    pub fn new_lazy<T>(identifier: Identifier, text: T) -> Self
+    where
+        T: Into<Text<'text>>,
+    {
+        let mut item = Self::new_leaf(identifier, text);
+        item.lazy = true;
+        item
+    }
+
     /// Create a new `TreeItem` with children.
     ///
     /// # Errors
@@ -77,11 +199,121 @@ where
             ));
         }
 
-        Ok(Self {
-            identifier,
-            text: text.into(),
-            children,
-        })
+        let mut item = Self::new_leaf(identifier, text);
+        item.children = children;
+        Ok(item)
+    }
+
+    /// Create the header row of one of multiple independent trees shown in one [`Tree`](crate::Tree), like the folders of a workspace.
+    ///
+    /// Only top level items can be headers.
+    /// A header is always open and rendered without indentation and symbol in the [`Tree::header_style`](crate::Tree::header_style).
+    /// As the identifiers of its children start with the identifier of the header, like `[root_index, "src"]`, the roots can contain the same identifiers without interfering with each other.
+    /// Use [`TreeState::set_skip_headers`](crate::TreeState::set_skip_headers) to not select headers when navigating.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are duplicate identifiers in the children.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::TreeItem;
+    /// let items = vec![
+    ///     TreeItem::new_header(0, "first", vec![TreeItem::new_leaf(0, "README")])?,
+    ///     TreeItem::new_header(1, "second", vec![TreeItem::new_leaf(0, "README")])?,
+    /// ];
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn new_header<T>(
+        identifier: Identifier,
+        text: T,
+        children: Vec<Self>,
+    ) -> std::io::Result<Self>
+    where
+        T: Into<Text<'text>>,
+    {
+        let mut item = Self::new(identifier, text, children)?;
+        item.header = true;
+        Ok(item)
+    }
+
+    /// Nest the given top level items under a single root item.
+    ///
+    /// The root can be used to collapse the whole tree.
+    /// All identifiers then start with the identifier of the root, like `["root", "child"]`.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are duplicate identifiers in the items.
+    pub fn wrap_in_root<T>(
+        items: Vec<Self>,
+        identifier: Identifier,
+        text: T,
+    ) -> std::io::Result<Vec<Self>>
+    where
+        T: Into<Text<'text>>,
+    {
+        Ok(vec![Self::new(identifier, text, items)?])
+    }
+
+    /// Only show `page_size` children at first followed by a row to show the next page.
+    ///
+    /// The row is rendered like `… show 42 more` and uses the `more_identifier` as its identifier.
+    /// So it can be selected like its siblings and activated with [`TreeState::enter`](crate::TreeState::enter) or a click.
+    /// The amount of shown pages is kept in the [`TreeState`](crate::TreeState), see [`TreeState::show_more`](crate::TreeState::show_more).
+    ///
+    /// # Errors
+    ///
+    /// Errors when the `more_identifier` already exists in the children.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::TreeItem;
+    /// let children = (0..100).map(|index| TreeItem::new_leaf(index, index.to_string())).collect();
+    /// let item = TreeItem::new(1000, "Numbers", children)?.paginated(10, 1001)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn paginated(
+        mut self,
+        page_size: usize,
+        more_identifier: Identifier,
+    ) -> std::io::Result<Self> {
+        if self
+            .children
+            .iter()
+            .any(|child| child.identifier == more_identifier)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "identifier already exists in the children",
+            ));
+        }
+        self.page_size = page_size.max(1);
+        self.show_more = Some(Box::new(Self::new_leaf(more_identifier, "…")));
+        Ok(self)
+    }
+
+    /// Set a one line description which is shown below the item while it is selected.
+    ///
+    /// Only shown with [`Tree::show_selected_description`](crate::Tree::show_selected_description).
+    #[must_use]
+    pub fn description<T>(mut self, description: T) -> Self
+    where
+        T: Into<Line<'text>>,
+    {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Mark an item without children as a branch, like an empty directory.
+    ///
+    /// It is rendered with the [`Tree::empty_branch_symbol`](crate::Tree::empty_branch_symbol) instead of the no children symbol.
+    #[must_use]
+    pub const fn expandable(mut self, expandable: bool) -> Self {
+        self.expandable = expandable;
+        self
     }
 
     /// Get a reference to the identifier.
@@ -111,6 +343,9 @@ where
 
     #[must_use]
     pub fn height(&self) -> usize {
+        if self.dynamic_text.is_some() {
+            return 1;
+        }
         self.text.height()
     }
 
@@ -125,7 +360,11 @@ where
             .iter()
             .map(|item| &item.identifier)
             .collect::<HashSet<_>>();
-        if existing.contains(&child.identifier) {
+        let is_show_more = self
+            .show_more
+            .as_ref()
+            .is_some_and(|more| more.identifier == child.identifier);
+        if existing.contains(&child.identifier) || is_show_more {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::AlreadyExists,
                 "identifier already exists in the children",
@@ -135,6 +374,120 @@ where
         self.children.push(child);
         Ok(())
     }
+
+    pub(super) fn find<'a>(items: &'a [Self], identifier: &[Identifier]) -> Option<&'a Self> {
+        let (first, rest) = identifier.split_first()?;
+        let mut item = items.iter().find(|item| &item.identifier == first)?;
+        for identifier in rest {
+            item = item
+                .children
+                .iter()
+                .find(|item| &item.identifier == identifier)?;
+        }
+        Some(item)
+    }
+
+    /// The paginated parent when the identifier is its [show more](Self::paginated) row.
+    pub(super) fn find_show_more_parent<'a>(
+        items: &'a [Self],
+        identifier: &[Identifier],
+    ) -> Option<&'a Self> {
+        let (last, parent) = identifier.split_last()?;
+        let parent = Self::find(items, parent)?;
+        parent
+            .show_more
+            .as_ref()
+            .is_some_and(|more| more.identifier == *last)
+            .then_some(parent)
+    }
+
+    /// The given identifiers which exist in the items, including [show more](Self::paginated) rows.
+    ///
+    /// Walks the items once and only descends into items which are a parent of one of the identifiers.
+    pub(super) fn existing<'p>(
+        items: &[Self],
+        identifiers: &[&'p [Identifier]],
+    ) -> HashSet<&'p [Identifier]> {
+        let mut existing = HashSet::new();
+        let mut stack = vec![(items, None, 0, identifiers.to_vec())];
+        while let Some((items, show_more, depth, candidates)) = stack.pop() {
+            let mut by_identifier = HashMap::<_, Vec<_>>::new();
+            for identifier in candidates {
+                by_identifier
+                    .entry(&identifier[depth])
+                    .or_default()
+                    .push(identifier);
+            }
+            for item in items.iter().chain(show_more) {
+                let Some(candidates) = by_identifier.remove(&item.identifier) else {
+                    continue;
+                };
+                let (found, deeper): (Vec<_>, Vec<_>) = candidates
+                    .into_iter()
+                    .partition(|identifier| identifier.len() == depth + 1);
+                existing.extend(found);
+                if !deeper.is_empty() {
+                    stack.push((&item.children, item.show_more.as_deref(), depth + 1, deeper));
+                }
+            }
+        }
+        existing
+    }
+
+    fn find_mut<'a>(items: &'a mut [Self], identifier: &[Identifier]) -> Option<&'a mut Self> {
+        let (first, rest) = identifier.split_first()?;
+        let mut item = items.iter_mut().find(|item| &item.identifier == first)?;
+        for identifier in rest {
+            item = item
+                .children
+                .iter_mut()
+                .find(|item| &item.identifier == identifier)?;
+        }
+        Some(item)
+    }
+
+    /// Replace the children of the `TreeItem` at the given identifier path.
+    ///
+    /// This is intended to install the loaded children of a [lazy](Self::new_lazy) `TreeItem`.
+    /// Afterwards it is no longer lazy, even when there are no children.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there is no item with the given identifier or there are duplicate identifiers in the children.
+    pub fn set_children_at(
+        items: &mut [Self],
+        identifier: &[Identifier],
+        children: Vec<Self>,
+    ) -> std::io::Result<()> {
+        let identifiers = children
+            .iter()
+            .map(|item| &item.identifier)
+            .collect::<HashSet<_>>();
+        if identifiers.len() != children.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "The children contain duplicate identifiers",
+            ));
+        }
+
+        let item = Self::find_mut(items, identifier).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "identifier does not exist in the items",
+            )
+        })?;
+        if let Some(more) = &item.show_more {
+            if identifiers.contains(&more.identifier) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "The children contain the identifier of the show more row",
+                ));
+            }
+        }
+        item.children = children;
+        item.lazy = false;
+        Ok(())
+    }
 }
 
 impl TreeItem<'static, &'static str> {
@@ -179,3 +532,83 @@ fn tree_item_add_child_errors_with_duplicate_identifiers() {
     let mut root = TreeItem::new("root", "Root", vec![item]).unwrap();
     root.add_child(another).unwrap();
 }
+
+#[test]
+fn tree_item_set_children_at() {
+    let mut items = TreeItem::example();
+    TreeItem::set_children_at(
+        &mut items,
+        &["b", "d"],
+        vec![TreeItem::new_leaf("x", "Xray")],
+    )
+    .unwrap();
+    assert_eq!(items[1].children[1].children.len(), 1);
+
+    let error = TreeItem::set_children_at(&mut items, &["b", "missing"], Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+
+    let item = TreeItem::new_leaf("same", "text");
+    let error =
+        TreeItem::set_children_at(&mut items, &["a"], vec![item.clone(), item]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+}
+
+#[test]
+#[should_panic = "identifier already exists"]
+fn tree_item_paginated_errors_with_existing_identifier() {
+    TreeItem::new("root", "Root", vec![TreeItem::new_leaf("more", "More")])
+        .unwrap()
+        .paginated(1, "more")
+        .unwrap();
+}
+
+#[test]
+fn tree_item_wrap_in_root() {
+    let items = TreeItem::wrap_in_root(TreeItem::example(), "root", "Document").unwrap();
+    assert_eq!(items.len(), 1);
+    let mut state = crate::TreeState::default();
+    state.select(vec!["root"]);
+    state.toggle_selected();
+    state.open(vec!["root", "b"]);
+    let identifiers = state
+        .flatten(&items)
+        .into_iter()
+        .map(|flattened| flattened.identifier)
+        .collect::<Vec<_>>();
+    assert_eq!(identifiers[0], ["root"]);
+    assert_eq!(identifiers[3], ["root", "b", "c"]);
+    assert_eq!(
+        TreeItem::find(&items, &["root"]).map(|item| item.children.len()),
+        Some(3)
+    );
+    assert_eq!(
+        crate::breadcrumb(&items, &identifiers[3], "/")
+            .unwrap()
+            .to_string(),
+        "Document/Bravo/Charlie"
+    );
+}
+
+#[test]
+fn tree_item_deep_clone_and_find() {
+    const DEPTH: usize = 100_000;
+    let mut item = TreeItem::new_leaf(0, "leaf");
+    for depth in 1..DEPTH {
+        item = TreeItem::new(depth, "nested", vec![item]).unwrap();
+    }
+    let mut items = vec![item.clone()];
+    drop(item);
+
+    let identifier = (0..DEPTH).rev().collect::<Vec<_>>();
+    let leaf = TreeItem::find(&items, &identifier).unwrap();
+    assert!(leaf.children.is_empty());
+    assert_eq!(leaf.identifier, 0);
+    assert!(TreeItem::find(&items, &identifier[..DEPTH / 2]).is_some());
+    assert!(TreeItem::find(&items, &[DEPTH - 1, 0]).is_none());
+
+    TreeItem::set_children_at(&mut items, &identifier, vec![TreeItem::new_leaf(0, "")]).unwrap();
+    assert_eq!(
+        TreeItem::find(&items, &identifier).unwrap().children.len(),
+        1
+    );
+}
@@ -1,10 +1,87 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use ratatui::layout::{Position, Rect};
 
-use crate::flatten::{flatten, Flattened};
+use crate::event::TreeEvent;
+use crate::flatten::{flatten, flatten_until, shown_children, Flattened, FlattenedIdentifier};
+use crate::path_set::PathSet;
 use crate::tree_item::TreeItem;
 
+/// What happened with [`TreeState::enter`].
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Enter<Identifier> {
+    /// Nothing is selected or nothing changed.
+    None,
+    /// The selected node was opened or closed.
+    /// Contains `true` when it is now open.
+    Toggled(bool),
+    /// The selected [`TreeItem`] was activated, like opening a file.
+    /// Contains its identifier.
+    Activated(Vec<Identifier>),
+    /// The show more row of a [paginated](TreeItem::paginated) node was activated.
+    /// The next page is shown and its first child selected.
+    NextPage,
+    /// A [bookmark](TreeState::toggle_bookmark_selected) was activated.
+    /// The bookmarked node is selected and its parents are opened.
+    Jumped,
+}
+
+/// What happened with [`TreeState::toggle_item`].
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToggleOutcome<Identifier> {
+    /// The identifier is empty or does not exist in the items.
+    None,
+    /// The node was opened or closed.
+    /// Contains `true` when it is now open.
+    Toggled(bool),
+    /// The [`TreeItem`] has no children and is not [lazy](TreeItem::new_lazy) so it was not opened.
+    /// Contains its identifier.
+    ///
+    /// Apps can use this to tell the user that a directory is empty for example.
+    NoChildren(Vec<Identifier>),
+    /// The [`TreeItem`] is at the [max path depth](TreeState::set_max_path_depth) so it was not opened.
+    /// Contains its identifier.
+    TooDeep(Vec<Identifier>),
+}
+
+/// Error of [`TreeState::try_open`] and [`TreeState::try_select`].
+///
+/// The identifier is too long for the [max path depth](TreeState::set_max_path_depth).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooDeep {
+    /// The max path depth the identifier exceeds
+    pub max_path_depth: usize,
+}
+
+impl std::fmt::Display for TooDeep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The identifier exceeds the max path depth of {}",
+            self.max_path_depth
+        )
+    }
+}
+
+impl std::error::Error for TooDeep {}
+
+/// How [`TreeState::enter`] handles nodes with children.
+///
+/// Leafs are always activated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EnterBehavior {
+    /// Open or close the node.
+    #[default]
+    Toggle,
+    /// Only open the node. Does nothing when it is already open.
+    Open,
+    /// Open or close the node and also activate it.
+    /// Returns [`Enter::Activated`], or [`Enter::None`] when the node could not be opened.
+    ToggleAndActivate,
+}
+
 /// Keeps the state of what is currently selected and what was opened in a [`Tree`](crate::Tree).
 ///
 /// The generic argument `Identifier` is used to keep the state like the currently selected or opened [`TreeItem`]s in the [`TreeState`].
@@ -19,19 +96,68 @@ use crate::tree_item::TreeItem;
 /// let mut state = TreeState::<Identifier>::default();
 /// ```
 #[must_use]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TreeState<Identifier> {
     pub(super) offset: usize,
-    pub(super) opened: HashSet<Vec<Identifier>>,
+    pub(super) opened: PathSet<Identifier>,
     pub(super) selected: Vec<Identifier>,
     pub(super) ensure_selected_in_view_on_next_render: bool,
+    pub(super) center_selected_on_next_render: bool,
+    /// Wrap around at the start / end with [`key_up`](Self::key_up) and [`key_down`](Self::key_down)
+    pub(super) wrap: bool,
+    /// Do not select [headers](TreeItem::new_header) with [`key_up`](Self::key_up) and [`key_down`](Self::key_down)
+    pub(super) skip_headers: bool,
+    /// How [`enter`](Self::enter) handles nodes with children
+    pub(super) enter_behavior: EnterBehavior,
+    /// Lazy items currently loading their children
+    pub(super) loading: HashSet<Vec<Identifier>>,
+    /// Amount of shown pages of paginated items
+    pub(super) pages: HashMap<Vec<Identifier>, usize>,
+    /// Keep the selected item on the same row when items before it change
+    pub(super) anchor_to_selected: bool,
+    /// Bookmarked items in the order they were bookmarked
+    pub(super) bookmarks: Vec<Vec<Identifier>>,
+    /// Bookmark row selected instead of an item of the tree
+    pub(super) selected_bookmark: Option<Vec<Identifier>>,
+    /// Item currently edited inline
+    pub(super) editing: Option<Vec<Identifier>>,
+    /// Items opened since the last render
+    pub(super) just_opened: Vec<Vec<Identifier>>,
+    /// Events pushed but not yet drained
+    pub(super) events: VecDeque<TreeEvent<Identifier>>,
+    /// Longest identifier which can be selected. Only shorter ones can be opened.
+    pub(super) max_path_depth: Option<usize>,
 
     pub(super) last_area: Rect,
     pub(super) last_biggest_index: usize,
+    /// Offset at which the last item is at the bottom of the view on last render
+    pub(super) last_max_offset: usize,
     /// All identifiers open on last render
-    pub(super) last_identifiers: Vec<Vec<Identifier>>,
+    pub(super) last_identifiers: Vec<FlattenedIdentifier<Identifier>>,
     /// Identifier rendered at `y` on last render
     pub(super) last_rendered_identifiers: Vec<(u16, Vec<Identifier>)>,
+    /// Opened lazy items without children on last render
+    pub(super) last_pending_loads: Vec<Vec<Identifier>>,
+    /// Headers shown on last render
+    pub(super) last_headers: Vec<Vec<Identifier>>,
+    /// Bookmarks which exist in the items and were shown on last render
+    pub(super) last_bookmarks: Vec<Vec<Identifier>>,
+    /// Selected identifier and its row within the area on last render
+    pub(super) last_selected_row: Option<(Vec<Identifier>, u16)>,
+    /// Hidden selected identifier and the index of the closest visible item in `last_identifiers` on last render
+    pub(super) last_hidden_selected: Option<(Vec<Identifier>, usize)>,
+
+    /// Height of each item in `last_identifiers` including placeholders
+    #[cfg(feature = "crossterm")]
+    pub(super) last_heights: Vec<usize>,
+
+    pub(super) undo: crate::undo::UndoState<Identifier>,
+
+    #[cfg(feature = "crossterm")]
+    pub(super) keys: crate::keyboard::KeyState,
+    #[cfg(feature = "crossterm")]
+    pub(super) mouse: crate::mouse::MouseState<Identifier>,
 }
 
 impl<Identifier> TreeState<Identifier>
@@ -46,11 +172,12 @@ where
     #[must_use]
     #[deprecated = "Use self.opened()"]
     pub fn get_all_opened(&self) -> Vec<Vec<Identifier>> {
-        self.opened.iter().cloned().collect()
+        self.opened.iter().collect()
     }
 
+    /// Identifiers of the opened nodes.
     #[must_use]
-    pub const fn opened(&self) -> &HashSet<Vec<Identifier>> {
+    pub const fn opened(&self) -> &PathSet<Identifier> {
         &self.opened
     }
 
@@ -65,12 +192,137 @@ where
         &self,
         items: &'text [TreeItem<'text, Identifier>],
     ) -> Vec<Flattened<'text, Identifier>> {
-        flatten(&self.opened, items, &[])
+        flatten(&self.opened, &self.pages, items)
+    }
+
+    /// Amount of currently viewable (including by scrolling) [`TreeItem`]s.
+    ///
+    /// Based on the last render.
+    /// The show more rows of [paginated](TreeItem::paginated) items are included, their hidden children are not.
+    #[must_use]
+    pub const fn visible_count(&self) -> usize {
+        self.last_identifiers.len()
+    }
+
+    /// Hash of everything the items in the view of the last render and this state contribute to the rendered result.
+    ///
+    /// When it did not change since the last render the [`Tree`](crate::Tree) would look the same, so rendering it can be skipped.
+    /// Only the items in the view are hashed and the items below it are not walked, so this stays cheap for big trees.
+    /// Changes to the [`Tree`](crate::Tree) itself like its styles or the size of its area are not included.
+    #[must_use]
+    pub fn render_signature(&self, items: &[TreeItem<Identifier>]) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.offset.hash(&mut hasher);
+        self.selected.hash(&mut hasher);
+        self.selected_bookmark.hash(&mut hasher);
+        self.bookmarks.hash(&mut hasher);
+        self.editing.hash(&mut hasher);
+
+        // Stop walking the items once the view is filled
+        let available_height = usize::from(self.last_area.height);
+        let mut index = 0;
+        let mut height = 0;
+        let visible = flatten_until(&self.opened, &self.pages, items, |item| {
+            if index >= self.offset {
+                height += item.height();
+            }
+            index += 1;
+            index > self.offset && height >= available_height
+        });
+
+        let mut height = 0;
+        for Flattened { identifier, item } in visible
+            .iter()
+            .skip(self.offset.min(visible.len().saturating_sub(1)))
+        {
+            identifier.hash(&mut hasher);
+            self.opened.contains(identifier).hash(&mut hasher);
+            self.loading.contains(&**identifier).hash(&mut hasher);
+            item.dynamic_text()
+                .as_ref()
+                .unwrap_or(&item.text)
+                .hash(&mut hasher);
+            item.description.hash(&mut hasher);
+            item.children.is_empty().hash(&mut hasher);
+            item.lazy.hash(&mut hasher);
+            item.expandable.hash(&mut hasher);
+
+            height += item.height();
+            if height >= available_height {
+                break;
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Amount of all [`TreeItem`]s including the children of closed ones.
+    #[must_use]
+    pub fn total_count(items: &[TreeItem<Identifier>]) -> usize {
+        let mut count = 0;
+        let mut stack = vec![items];
+        while let Some(items) = stack.pop() {
+            count += items.len();
+            stack.extend(items.iter().map(|item| item.children.as_slice()));
+        }
+        count
+    }
+
+    /// Position of the selected [`TreeItem`] in the [`visible_count`](Self::visible_count) items starting with 1.
+    ///
+    /// Based on the last render.
+    /// Returns `None` when nothing is selected or the selection was not visible.
+    #[must_use]
+    pub fn selected_position(&self) -> Option<usize> {
+        if self.selected.is_empty() {
+            return None;
+        }
+        self.last_identifiers
+            .iter()
+            .position(|identifier| *identifier == self.selected)
+            .map(|index| index + 1)
+    }
+
+    /// Opened [lazy](TreeItem::new_lazy) [`TreeItem`]s which need their children loaded.
+    ///
+    /// Based on the last render.
+    /// Items which were closed in the meantime or are [marked as loading](Self::mark_loading) are not included.
+    #[must_use]
+    pub fn pending_loads(&self) -> Vec<Vec<Identifier>> {
+        self.last_pending_loads
+            .iter()
+            .filter(|identifier| {
+                self.opened.contains(identifier) && !self.loading.contains(*identifier)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Mark a [lazy](TreeItem::new_lazy) [`TreeItem`] as currently loading its children.
+    ///
+    /// While it is open and has no children a placeholder is rendered below it, see [`Tree::loading_placeholder`](crate::Tree::loading_placeholder).
+    /// The mark is removed once the children are installed with [`TreeItem::set_children_at`] or the item is closed.
+    ///
+    /// Returns `true` when it was not marked before.
+    pub fn mark_loading(&mut self, identifier: Vec<Identifier>) -> bool {
+        self.loading.insert(identifier)
+    }
+
+    /// Whether the loading placeholder is shown below this item.
+    pub(super) fn shows_loading_placeholder(&self, flattened: &Flattened<Identifier>) -> bool {
+        let Flattened { identifier, item } = flattened;
+        item.lazy
+            && item.children.is_empty()
+            && self.opened.contains(identifier)
+            && self.loading.contains(&**identifier)
     }
 
     /// Selects the given identifier.
     ///
     /// Returns `true` when the selection changed.
+    /// Returns `false` without selecting when the identifier is longer than the [max path depth](Self::set_max_path_depth).
+    /// Use [`try_select`](Self::try_select) to tell both apart.
     ///
     /// Clear the selection by passing an empty identifier vector:
     ///
@@ -80,35 +332,141 @@ where
     /// state.select(Vec::new());
     /// ```
     pub fn select(&mut self, identifier: Vec<Identifier>) -> bool {
-        self.ensure_selected_in_view_on_next_render = true;
-        let changed = self.selected != identifier;
-        self.selected = identifier;
-        changed
+        self.try_select(identifier).unwrap_or(false)
+    }
+
+    /// Selects the given identifier like [`select`](Self::select).
+    ///
+    /// Returns `true` when the selection changed.
+    ///
+    /// # Errors
+    ///
+    /// Errors without selecting when the identifier is longer than the [max path depth](Self::set_max_path_depth).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tui_tree_widget::{TooDeep, TreeState};
+    /// let mut state = TreeState::default();
+    /// state.set_max_path_depth(2);
+    /// assert_eq!(state.try_select(vec!["a", "b"]), Ok(true));
+    /// assert_eq!(state.try_select(vec!["a", "b", "c"]), Err(TooDeep { max_path_depth: 2 }));
+    /// ```
+    pub fn try_select(&mut self, identifier: Vec<Identifier>) -> Result<bool, TooDeep> {
+        if let Some(max_path_depth) = self.max_path_depth.filter(|max| identifier.len() > *max) {
+            return Err(TooDeep { max_path_depth });
+        }
+        Ok(self.undoable(|state| {
+            state.ensure_selected_in_view_on_next_render = true;
+            let changed = state.selected != identifier || state.selected_bookmark.is_some();
+            state.selected = identifier;
+            state.selected_bookmark = None;
+            changed
+        }))
     }
 
     /// Open a tree node.
     /// Returns `true` when it was closed and has been opened.
-    /// Returns `false` when it was already open.
+    /// Returns `false` when it was already open or is at the [max path depth](Self::set_max_path_depth).
+    /// Use [`try_open`](Self::try_open) to tell both apart.
     pub fn open(&mut self, identifier: Vec<Identifier>) -> bool {
-        if identifier.is_empty() {
-            false
-        } else {
-            self.opened.insert(identifier)
+        self.try_open(identifier).unwrap_or(false)
+    }
+
+    /// Open a tree node like [`open`](Self::open).
+    ///
+    /// Returns `true` when it was closed and has been opened.
+    ///
+    /// # Errors
+    ///
+    /// Errors without opening when the identifier is at least as long as the [max path depth](Self::set_max_path_depth).
+    pub fn try_open(&mut self, identifier: Vec<Identifier>) -> Result<bool, TooDeep> {
+        if let Some(max_path_depth) = self
+            .max_path_depth
+            .filter(|_| self.is_too_deep(&identifier))
+        {
+            return Err(TooDeep { max_path_depth });
+        }
+        if identifier.is_empty() || self.opened.contains(&identifier) {
+            return Ok(false);
         }
+        Ok(self.undoable(|state| {
+            state.just_opened.push(identifier.clone());
+            state.insert_opened(identifier)
+        }))
+    }
+
+    /// Open a tree node and its descendants up to the given amount of levels.
+    ///
+    /// Depth 1 only opens the given node like [`open`](Self::open).
+    /// Depth 2 also opens its children, so its grandchildren become visible, and so on.
+    /// Descendants without children are not opened.
+    ///
+    /// Returns the amount of nodes which were closed and have been opened.
+    pub fn open_recursive(
+        &mut self,
+        items: &[TreeItem<Identifier>],
+        identifier: &[Identifier],
+        depth: usize,
+    ) -> usize {
+        self.undoable(|state| state.open_recursive_inner(items, identifier, depth))
+    }
+
+    fn open_recursive_inner(
+        &mut self,
+        items: &[TreeItem<Identifier>],
+        identifier: &[Identifier],
+        depth: usize,
+    ) -> usize {
+        if depth == 0 {
+            return 0;
+        }
+        let count = usize::from(self.open(identifier.to_vec()));
+        // Nodes which are too deep were not opened and neither are their descendants
+        let node = self
+            .opened
+            .find(identifier)
+            .filter(|node| self.opened.contains_node(*node));
+        let (Some(item), Some(node)) = (TreeItem::find(items, identifier), node) else {
+            return count;
+        };
+
+        // Open the descendants by their node to not look up their whole identifier every time
+        let mut descendants = 0;
+        let mut stack = vec![(node, item, identifier.len(), depth)];
+        while let Some((node, item, length, depth)) = stack.pop() {
+            if depth <= 1 || self.max_path_depth.is_some_and(|max| length + 1 >= max) {
+                continue;
+            }
+            for child in &item.children {
+                if child.children.is_empty() {
+                    continue;
+                }
+                let (child_node, opened) = self.insert_opened_child(node, &child.identifier);
+                descendants += usize::from(opened);
+                stack.push((child_node, child, length + 1, depth - 1));
+            }
+        }
+        // Revealing the given node also reveals its descendants
+        if descendants > 0 && count == 0 {
+            self.just_opened.push(identifier.to_vec());
+        }
+        count + descendants
     }
 
     /// Close a tree node.
     /// Returns `true` when it was open and has been closed.
     /// Returns `false` when it was already closed.
     pub fn close(&mut self, identifier: &[Identifier]) -> bool {
-        self.opened.remove(identifier)
+        self.undoable(|state| state.remove_opened(identifier))
     }
 
     /// Toggles a tree node open/close state.
     /// When it is currently open, then [`close`](Self::close) is called. Otherwise [`open`](Self::open).
     ///
     /// Returns `true` when a node is opened / closed.
-    /// As toggle always changes something, this only returns `false` when an empty identifier is given.
+    /// Returns `false` when an empty identifier is given or opening is rejected by the [max path depth](Self::set_max_path_depth).
+    /// Use [`try_open`](Self::try_open) to get the [`TooDeep`] error instead.
     pub fn toggle(&mut self, identifier: Vec<Identifier>) -> bool {
         if identifier.is_empty() {
             false
@@ -119,11 +477,42 @@ where
         }
     }
 
+    /// Toggles a tree node open/close state like [`toggle`](Self::toggle) but knows about the items.
+    ///
+    /// Items without children which are not [lazy](TreeItem::new_lazy) are not opened and return [`ToggleOutcome::NoChildren`].
+    pub fn toggle_item(
+        &mut self,
+        items: &[TreeItem<Identifier>],
+        identifier: Vec<Identifier>,
+    ) -> ToggleOutcome<Identifier> {
+        let Some(item) = TreeItem::find(items, &identifier) else {
+            return ToggleOutcome::None;
+        };
+        if item.children.is_empty() && !item.lazy {
+            return ToggleOutcome::NoChildren(identifier);
+        }
+        if self.is_too_deep(&identifier) {
+            return ToggleOutcome::TooDeep(identifier);
+        }
+        let open = !self.opened.contains(&identifier);
+        self.toggle(identifier);
+        ToggleOutcome::Toggled(open)
+    }
+
+    /// Toggles the currently selected tree node open/close state like [`toggle_item`](Self::toggle_item).
+    pub fn toggle_selected_item(
+        &mut self,
+        items: &[TreeItem<Identifier>],
+    ) -> ToggleOutcome<Identifier> {
+        self.ensure_selected_in_view_on_next_render = true;
+        self.toggle_item(items, self.selected.clone())
+    }
+
     /// Toggles the currently selected tree node open/close state.
     /// See also [`toggle`](Self::toggle)
     ///
     /// Returns `true` when a node is opened / closed.
-    /// As toggle always changes something, this only returns `false` when nothing is selected.
+    /// Returns `false` when nothing is selected or opening is rejected by the [max path depth](Self::set_max_path_depth).
     pub fn toggle_selected(&mut self) -> bool {
         if self.selected.is_empty() {
             return false;
@@ -131,13 +520,201 @@ where
 
         self.ensure_selected_in_view_on_next_render = true;
 
-        // Reimplement self.close because of multiple different borrows
-        let was_open = self.opened.remove(&self.selected);
-        if was_open {
+        let selected = self.selected.clone();
+        if self.close(&selected) {
             return true;
         }
 
-        self.open(self.selected.clone())
+        self.open(selected)
+    }
+
+    /// Bookmark the selected node or remove its bookmark.
+    ///
+    /// Bookmarks are shown above the tree with [`Tree::show_bookmarks`](crate::Tree::show_bookmarks).
+    /// When a bookmark row is selected its bookmark is removed.
+    ///
+    /// Returns `true` when the node is now bookmarked.
+    pub fn toggle_bookmark_selected(&mut self) -> bool {
+        if let Some(bookmark) = self.selected_bookmark.take() {
+            self.bookmarks.retain(|existing| *existing != bookmark);
+            return false;
+        }
+        if self.selected.is_empty() {
+            return false;
+        }
+        if let Some(index) = self
+            .bookmarks
+            .iter()
+            .position(|bookmark| *bookmark == self.selected)
+        {
+            self.bookmarks.remove(index);
+            false
+        } else {
+            self.bookmarks.push(self.selected.clone());
+            true
+        }
+    }
+
+    /// All bookmarked nodes in the order they were bookmarked.
+    ///
+    /// This can contain bookmarks which no longer exist in the items.
+    #[must_use]
+    pub fn bookmarks(&self) -> &[Vec<Identifier>] {
+        &self.bookmarks
+    }
+
+    /// The bookmark whose row is selected instead of a node of the tree.
+    ///
+    /// While a bookmark row is selected, [`selected`](Self::selected) is empty.
+    #[must_use]
+    pub fn selected_bookmark(&self) -> Option<&[Identifier]> {
+        self.selected_bookmark.as_deref()
+    }
+
+    /// Move the selection through the bookmark rows and the tree when bookmarks were shown on last render.
+    ///
+    /// Returns `None` when no bookmarks were shown.
+    fn select_relative_with_bookmarks(&mut self, down: bool) -> Option<bool> {
+        let bookmarks = self.last_bookmarks.len();
+        if bookmarks == 0 {
+            return None;
+        }
+        if let Some(changed) = self.select_visible_instead_of_hidden() {
+            return Some(changed);
+        }
+        let total = bookmarks + self.last_identifiers.len();
+        let current = self.selected_bookmark.as_ref().map_or_else(
+            || {
+                self.last_identifiers
+                    .iter()
+                    .position(|identifier| *identifier == self.selected)
+                    .map(|index| index + bookmarks)
+            },
+            |selected| {
+                self.last_bookmarks
+                    .iter()
+                    .position(|bookmark| bookmark == selected)
+            },
+        );
+        let new_index = match (current, down) {
+            (None, true) => 0,
+            (None, false) => total - 1,
+            (Some(current), true) if current + 1 >= total => {
+                if self.wrap {
+                    0
+                } else {
+                    current
+                }
+            }
+            (Some(current), true) => current + 1,
+            (Some(0), false) => {
+                if self.wrap {
+                    total - 1
+                } else {
+                    0
+                }
+            }
+            (Some(current), false) => current - 1,
+        };
+
+        if let Some(bookmark) = self.last_bookmarks.get(new_index) {
+            let changed = self.selected_bookmark.as_ref() != Some(bookmark);
+            self.selected_bookmark = Some(bookmark.clone());
+            self.selected.clear();
+            Some(changed)
+        } else {
+            let identifier = self.last_identifiers[new_index - bookmarks].to_vec();
+            Some(self.select(identifier))
+        }
+    }
+
+    /// Show the next page of children of a [paginated](TreeItem::paginated) node.
+    ///
+    /// Returns `false` when an empty identifier is given.
+    pub fn show_more(&mut self, identifier: Vec<Identifier>) -> bool {
+        if identifier.is_empty() {
+            return false;
+        }
+        *self.pages.entry(identifier).or_insert(1) += 1;
+        true
+    }
+
+    /// Only show the first page of all [paginated](TreeItem::paginated) nodes again.
+    pub fn reset_pagination(&mut self) {
+        self.pages.clear();
+    }
+
+    /// Handle the Enter key on the currently selected node.
+    ///
+    /// Nodes with children are handled based on [`set_enter_behavior`](Self::set_enter_behavior) and toggle by default.
+    /// Leafs are activated so the app can for example open the file.
+    /// The show more row of a [paginated](TreeItem::paginated) node shows the next page.
+    /// A selected [bookmark](Self::toggle_bookmark_selected) row jumps to the bookmarked node.
+    /// [Lazy](TreeItem::new_lazy) nodes are not leafs.
+    pub fn enter(&mut self, items: &[TreeItem<Identifier>]) -> Enter<Identifier> {
+        if self.selected.is_empty() && self.selected_bookmark.is_none() {
+            return Enter::None;
+        }
+
+        if let Some(bookmark) = self.selected_bookmark.take() {
+            self.undoable(|state| {
+                for length in 1..bookmark.len() {
+                    state.open(bookmark[..length].to_vec());
+                }
+                state.select(bookmark);
+            });
+            return Enter::Jumped;
+        }
+
+        if let Some(parent) = TreeItem::find_show_more_parent(items, &self.selected) {
+            let mut identifier = self.selected.clone();
+            identifier.pop();
+            let shown = shown_children(&self.pages, &identifier, parent);
+            self.show_more(identifier.clone());
+            if let Some(first) = parent.children.get(shown) {
+                identifier.push(first.identifier.clone());
+                self.select(identifier);
+            }
+            return Enter::NextPage;
+        }
+
+        let is_leaf = TreeItem::find(items, &self.selected)
+            .is_none_or(|item| item.children.is_empty() && !item.lazy);
+        if is_leaf {
+            return Enter::Activated(self.selected.clone());
+        }
+
+        match self.enter_behavior {
+            EnterBehavior::Toggle => {
+                if self.toggle_selected() {
+                    Enter::Toggled(self.opened.contains(&self.selected))
+                } else {
+                    Enter::None
+                }
+            }
+            EnterBehavior::Open => {
+                self.ensure_selected_in_view_on_next_render = true;
+                if self.open(self.selected.clone()) {
+                    Enter::Toggled(true)
+                } else {
+                    Enter::None
+                }
+            }
+            EnterBehavior::ToggleAndActivate => {
+                if self.toggle_selected() {
+                    Enter::Activated(self.selected.clone())
+                } else {
+                    Enter::None
+                }
+            }
+        }
+    }
+
+    /// How [`enter`](Self::enter) handles nodes with children.
+    ///
+    /// Defaults to [`EnterBehavior::Toggle`].
+    pub const fn set_enter_behavior(&mut self, behavior: EnterBehavior) {
+        self.enter_behavior = behavior;
     }
 
     /// Closes all open nodes.
@@ -147,25 +724,216 @@ where
         if self.opened.is_empty() {
             false
         } else {
-            self.opened.clear();
+            self.undoable(|state| state.retain_opened(|_| false));
             true
         }
     }
 
+    /// Forget the state of [`TreeItem`]s which no longer exist in the given items.
+    ///
+    /// Opened, loading, paginated and bookmarked items which no longer exist are removed.
+    /// When the selected item no longer exists its closest existing parent is selected.
+    /// Call this after changing the items when the removed items might be added again later.
+    ///
+    /// Returns `true` when something was removed.
+    pub fn prune(&mut self, items: &[TreeItem<Identifier>]) -> bool {
+        let exists = |identifier: &[Identifier]| {
+            TreeItem::find(items, identifier).is_some()
+                || TreeItem::find_show_more_parent(items, identifier).is_some()
+        };
+        let before = (
+            self.opened.len(),
+            self.loading.len(),
+            self.pages.len(),
+            self.bookmarks.len(),
+        );
+        self.retain_opened(exists);
+        self.loading.retain(|identifier| exists(identifier));
+        self.pages.retain(|identifier, _| exists(identifier));
+        self.bookmarks.retain(|identifier| exists(identifier));
+        let after = (
+            self.opened.len(),
+            self.loading.len(),
+            self.pages.len(),
+            self.bookmarks.len(),
+        );
+
+        let removed_bookmark = self
+            .selected_bookmark
+            .take_if(|identifier| !exists(identifier))
+            .is_some();
+        let removed_editing = self
+            .editing
+            .take_if(|identifier| !exists(identifier))
+            .is_some();
+
+        let mut removed_selected = false;
+        while !self.selected.is_empty() && !exists(&self.selected) {
+            self.selected.pop();
+            self.ensure_selected_in_view_on_next_render = true;
+            removed_selected = true;
+        }
+        before != after || removed_bookmark || removed_editing || removed_selected
+    }
+
     /// Select the first node.
     ///
     /// Returns `true` when the selection changed.
     pub fn select_first(&mut self) -> bool {
-        let identifier = self.last_identifiers.first().cloned().unwrap_or_default();
-        self.select(identifier)
+        let identifier = self
+            .last_identifiers
+            .first()
+            .map(|identifier| identifier.to_vec())
+            .unwrap_or_default();
+        let before = self.selected.clone();
+        self.select(identifier);
+        self.skip_header(true, &before)
     }
 
     /// Select the last node.
     ///
     /// Returns `true` when the selection changed.
     pub fn select_last(&mut self) -> bool {
-        let new_identifier = self.last_identifiers.last().cloned().unwrap_or_default();
-        self.select(new_identifier)
+        let new_identifier = self
+            .last_identifiers
+            .last()
+            .map(|identifier| identifier.to_vec())
+            .unwrap_or_default();
+        let before = self.selected.clone();
+        self.select(new_identifier);
+        self.skip_header(false, &before)
+    }
+
+    /// Close all sibling branches of the selected node, the selected node itself stays as it is.
+    /// When a top level node is selected all other top level branches are closed.
+    ///
+    /// Returns the amount of branches which were open and have been closed.
+    pub fn close_siblings(&mut self, items: &[TreeItem<Identifier>]) -> usize {
+        let Some(siblings) = self.sibling_branches(items) else {
+            return 0;
+        };
+        let selected = self.selected.clone();
+        self.undoable(|state| {
+            siblings
+                .into_iter()
+                .filter(|identifier| *identifier != selected)
+                .filter(|identifier| state.close(identifier))
+                .count()
+        })
+    }
+
+    /// Open the selected node and all its sibling branches.
+    /// When a top level node is selected all top level branches are opened.
+    ///
+    /// Returns the amount of branches which were closed and have been opened.
+    pub fn open_siblings(&mut self, items: &[TreeItem<Identifier>]) -> usize {
+        let Some(siblings) = self.sibling_branches(items) else {
+            return 0;
+        };
+        self.undoable(|state| {
+            siblings
+                .into_iter()
+                .filter(|identifier| state.open(identifier.clone()))
+                .count()
+        })
+    }
+
+    /// Identifiers of the selected node and its siblings which are branches.
+    fn sibling_branches(&self, items: &[TreeItem<Identifier>]) -> Option<Vec<Vec<Identifier>>> {
+        let (_, parent) = self.selected.split_last()?;
+        let siblings = if parent.is_empty() {
+            items
+        } else {
+            &TreeItem::find(items, parent)?.children
+        };
+        let branches = siblings
+            .iter()
+            .filter(|item| !item.children.is_empty() || item.lazy)
+            .map(|item| {
+                let mut identifier = parent.to_vec();
+                identifier.push(item.identifier.clone());
+                identifier
+            })
+            .collect();
+        Some(branches)
+    }
+
+    /// Select the first visible sibling of the selected node.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_first_sibling(&mut self, items: &[TreeItem<Identifier>]) -> bool {
+        let Some(parent) = self.selected.split_last().map(|(_, parent)| parent) else {
+            return false;
+        };
+        let first = self
+            .flatten(items)
+            .into_iter()
+            .map(|flattened| flattened.identifier)
+            .find(|identifier| is_child_of(identifier, parent));
+        first.is_some_and(|identifier| self.select(identifier.to_vec()))
+    }
+
+    /// Select the last visible sibling of the selected node.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_last_sibling(&mut self, items: &[TreeItem<Identifier>]) -> bool {
+        let Some(parent) = self.selected.split_last().map(|(_, parent)| parent) else {
+            return false;
+        };
+        let last = self
+            .flatten(items)
+            .into_iter()
+            .map(|flattened| flattened.identifier)
+            .filter(|identifier| is_child_of(identifier, parent))
+            .last();
+        last.is_some_and(|identifier| self.select(identifier.to_vec()))
+    }
+
+    /// Jump between an open node and its last visible descendant, like jumping between matching brackets.
+    ///
+    /// On an open node with visible children its last visible descendant is selected.
+    /// On the last visible descendant of an open node that node is selected.
+    /// When it is the last visible descendant of multiple nodes the nearest one is selected.
+    /// Otherwise nothing happens.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn select_matching_end(&mut self, items: &[TreeItem<Identifier>]) -> bool {
+        if self.selected.is_empty() {
+            return false;
+        }
+        let visible = self
+            .flatten(items)
+            .into_iter()
+            .map(|flattened| flattened.identifier)
+            .collect::<Vec<_>>();
+        let Some(index) = visible
+            .iter()
+            .position(|identifier| *identifier == self.selected)
+        else {
+            return false;
+        };
+
+        let last_descendant = |index: usize| {
+            let identifier = &visible[index];
+            visible[index + 1..]
+                .iter()
+                .take_while(|other| other.len() > identifier.len() && other.starts_with(identifier))
+                .last()
+        };
+
+        if let Some(end) = last_descendant(index) {
+            return self.select(end.to_vec());
+        }
+
+        let start = (1..self.selected.len()).rev().find_map(|length| {
+            let ancestor = &self.selected[..length];
+            let ancestor_index = visible[..index]
+                .iter()
+                .rposition(|identifier| identifier == ancestor)?;
+            (last_descendant(ancestor_index).is_some_and(|end| *end == self.selected))
+                .then(|| ancestor.to_vec())
+        });
+        start.is_some_and(|identifier| self.select(identifier))
     }
 
     /// Select the node on the given index.
@@ -179,7 +947,7 @@ where
         let new_identifier = self
             .last_identifiers
             .get(new_index)
-            .cloned()
+            .map(|identifier| identifier.to_vec())
             .unwrap_or_default();
         self.select(new_identifier)
     }
@@ -215,7 +983,10 @@ where
             .iter()
             .position(|identifier| identifier == current_identifier);
         let new_index = change_function(current_index).min(self.last_biggest_index);
-        let new_identifier = identifiers.get(new_index).cloned().unwrap_or_default();
+        let new_identifier = identifiers
+            .get(new_index)
+            .map(|identifier| identifier.to_vec())
+            .unwrap_or_default();
         self.select(new_identifier)
     }
 
@@ -243,16 +1014,35 @@ where
     where
         F: FnOnce(Option<usize>) -> usize,
     {
+        if let Some(changed) = self.select_visible_instead_of_hidden() {
+            return changed;
+        }
         let identifiers = &self.last_identifiers;
         let current_identifier = &self.selected;
         let current_index = identifiers
             .iter()
             .position(|identifier| identifier == current_identifier);
         let new_index = change_function(current_index).min(self.last_biggest_index);
-        let new_identifier = identifiers.get(new_index).cloned().unwrap_or_default();
+        let new_identifier = identifiers
+            .get(new_index)
+            .map(|identifier| identifier.to_vec())
+            .unwrap_or_default();
         self.select(new_identifier)
     }
 
+    /// Select the closest visible item when the selected one was [hidden](crate::Tree::hide_fn) on last render.
+    ///
+    /// Returns `None` when the selected item was not hidden.
+    fn select_visible_instead_of_hidden(&mut self) -> Option<bool> {
+        let closest = self
+            .last_hidden_selected
+            .as_ref()
+            .filter(|(hidden, _)| *hidden == self.selected)
+            .and_then(|(_, closest)| self.last_identifiers.get(*closest))?
+            .to_vec();
+        Some(self.select(closest))
+    }
+
     /// Get the identifier that was rendered for the given position on last render.
     #[must_use]
     pub fn rendered_at(&self, position: Position) -> Option<&[Identifier]> {
@@ -285,15 +1075,24 @@ where
     }
 
     /// Ensure the selected [`TreeItem`] is in view on next render
-    pub fn scroll_selected_into_view(&mut self) {
+    pub const fn scroll_selected_into_view(&mut self) {
         self.ensure_selected_in_view_on_next_render = true;
     }
 
+    /// Scroll the selected [`TreeItem`] into the vertical center of the view on next render
+    ///
+    /// Near the start and end of the tree the offset is clamped so there is no empty space.
+    /// Also see [`Tree::scroll_mode`](crate::Tree::scroll_mode) to always center the selected item.
+    pub const fn center_selected(&mut self) {
+        self.ensure_selected_in_view_on_next_render = true;
+        self.center_selected_on_next_render = true;
+    }
+
     /// Scroll the specified amount of lines up
     ///
     /// Returns `true` when the scroll position changed.
     /// Returns `false` when the scrolling has reached the top.
-    pub fn scroll_up(&mut self, lines: usize) -> bool {
+    pub const fn scroll_up(&mut self, lines: usize) -> bool {
         let before = self.offset;
         self.offset = self.offset.saturating_sub(lines);
         before != self.offset
@@ -312,42 +1111,242 @@ where
         before != self.offset
     }
 
+    /// Scroll position between `0.0` (top) and `1.0` (bottom) based on the last render.
+    ///
+    /// This can be shown as a percentage like in `less` or used for a minimap.
+    /// Returns `None` before the first render or when all items fit into the view.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn scroll_ratio(&self) -> Option<f64> {
+        if self.last_area.height == 0 || self.last_max_offset == 0 {
+            return None;
+        }
+        let ratio = self.offset as f64 / self.last_max_offset as f64;
+        Some(ratio.clamp(0.0, 1.0))
+    }
+
+    /// Scroll to the position between `0.0` (top) and `1.0` (bottom) based on the last render.
+    /// Values outside are clamped.
+    ///
+    /// The selected item is not scrolled into view on the next render.
+    ///
+    /// Returns `true` when the scroll position changed.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn scroll_to_ratio(&mut self, ratio: f64) -> bool {
+        let ratio = if ratio.is_nan() {
+            0.0
+        } else {
+            ratio.clamp(0.0, 1.0)
+        };
+        let before = self.offset;
+        self.offset = (ratio * self.last_max_offset as f64).round() as usize;
+        self.ensure_selected_in_view_on_next_render = false;
+        before != self.offset
+    }
+
+    /// Keep the selected [`TreeItem`] on the same row of the view when items before it are inserted or removed.
+    ///
+    /// On render the offset is adapted so the selected item is rendered on the same row as on the last render.
+    /// When the selection changed or the selected item was not rendered last time the offset is not adapted.
+    ///
+    /// Defaults to `false`.
+    pub const fn anchor_to_selected(&mut self, anchor: bool) {
+        self.anchor_to_selected = anchor;
+    }
+
+    /// Start editing the selected [`TreeItem`] inline.
+    ///
+    /// While editing, the [`Tree::edit_text`](crate::Tree::edit_text) is rendered instead of the text of the item.
+    /// The app owns the text and handles the keys until [`end_edit`](Self::end_edit) is called.
+    ///
+    /// Returns `false` when nothing is selected.
+    pub fn begin_edit(&mut self) -> bool {
+        if self.selected.is_empty() {
+            return false;
+        }
+        self.ensure_selected_in_view_on_next_render = true;
+        self.editing = Some(self.selected.clone());
+        true
+    }
+
+    /// Stop editing inline.
+    ///
+    /// Returns `true` when something was edited.
+    pub fn end_edit(&mut self) -> bool {
+        self.editing.take().is_some()
+    }
+
+    /// The [`TreeItem`] currently edited inline, see [`begin_edit`](Self::begin_edit).
+    #[must_use]
+    pub fn editing(&self) -> Option<&[Identifier]> {
+        self.editing.as_deref()
+    }
+
+    /// Wrap around to the other end when moving beyond the first or last visible [`TreeItem`] with [`key_up`](Self::key_up) or [`key_down`](Self::key_down).
+    ///
+    /// Defaults to `false`.
+    /// This can be helpful for small trees used like menus.
+    pub const fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Do not select [headers](TreeItem::new_header) when navigating with [`key_up`](Self::key_up), [`key_down`](Self::key_down), [`select_first`](Self::select_first) and [`select_last`](Self::select_last).
+    ///
+    /// Defaults to `false`.
+    pub const fn set_skip_headers(&mut self, skip: bool) {
+        self.skip_headers = skip;
+    }
+
+    /// Limit the length of identifiers which can be opened or selected.
+    ///
+    /// This protects against misbehaving data with very deeply nested [`TreeItem`]s as the selection, bookmarks and loading items keep their whole identifier.
+    /// Opened nodes share the identifiers of their parents, see [`PathSet`].
+    /// Nodes with identifiers of this length can be selected but not opened, their children are hidden behind a [marker](crate::Tree::depth_limit_marker).
+    /// Opened nodes and the selection deeper than the limit are forgotten.
+    ///
+    /// Defaults to no limit.
+    pub fn set_max_path_depth(&mut self, max_depth: usize) {
+        self.max_path_depth = Some(max_depth);
+        self.retain_opened(|identifier| identifier.len() < max_depth);
+        self.loading
+            .retain(|identifier| identifier.len() < max_depth);
+        self.selected.truncate(max_depth);
+    }
+
+    /// Whether the node can not be opened because of the [max path depth](Self::set_max_path_depth).
+    fn is_too_deep(&self, identifier: &[Identifier]) -> bool {
+        self.max_path_depth
+            .is_some_and(|max| identifier.len() >= max)
+    }
+
+    /// Whether the depth limit marker is shown below this item.
+    pub(super) fn shows_depth_limit(&self, flattened: &Flattened<Identifier>) -> bool {
+        let Flattened { identifier, item } = flattened;
+        (item.lazy || !item.children.is_empty()) && self.is_too_deep(identifier)
+    }
+
+    /// When a header is selected but should be skipped, select the next item which is not a header in the given direction.
+    /// When there is no such item the selection `before` is restored.
+    ///
+    /// Returns `true` when the selection changed compared to `before`.
+    fn skip_header(&mut self, down: bool, before: &[Identifier]) -> bool {
+        if !self.skip_headers || !self.last_headers.contains(&self.selected) {
+            return self.selected != before;
+        }
+        let index = self
+            .last_identifiers
+            .iter()
+            .position(|identifier| *identifier == self.selected)
+            .unwrap_or_default();
+        let is_item = |identifier: &&FlattenedIdentifier<Identifier>| {
+            !self.last_headers.iter().any(|header| *identifier == header)
+        };
+        let next = if down {
+            self.last_identifiers[index + 1..].iter().find(is_item)
+        } else {
+            self.last_identifiers[..index].iter().rev().find(is_item)
+        };
+        let next = next.map_or_else(|| before.to_vec(), |identifier| identifier.to_vec());
+        self.select(next);
+        self.selected != before
+    }
+
     /// Handles the up arrow key.
     /// Moves up in the current depth or to its parent.
+    /// When [wrapping](Self::set_wrap) it moves from the first to the last item.
+    /// When [bookmarks](Self::toggle_bookmark_selected) are shown it also moves through their rows.
     ///
     /// Returns `true` when the selection changed.
     pub fn key_up(&mut self) -> bool {
-        self.select_relative(|current| {
+        if let Some(changed) = self.select_relative_with_bookmarks(false) {
+            return changed;
+        }
+        let wrap = self.wrap;
+        let before = self.selected.clone();
+        self.select_relative(|current| match current {
             // When nothing is selected, fall back to end
-            current.map_or(usize::MAX, |current| current.saturating_sub(1))
-        })
+            None => usize::MAX,
+            Some(0) if wrap => usize::MAX,
+            Some(current) => current.saturating_sub(1),
+        });
+        self.skip_header(false, &before)
     }
 
     /// Handles the down arrow key.
     /// Moves down in the current depth or into a child node.
+    /// When [wrapping](Self::set_wrap) it moves from the last to the first item.
+    /// When [bookmarks](Self::toggle_bookmark_selected) are shown it also moves through their rows.
     ///
     /// Returns `true` when the selection changed.
     pub fn key_down(&mut self) -> bool {
-        self.select_relative(|current| {
+        if let Some(changed) = self.select_relative_with_bookmarks(true) {
+            return changed;
+        }
+        let wrap = self.wrap;
+        let last = self.last_biggest_index;
+        let before = self.selected.clone();
+        self.select_relative(|current| match current {
             // When nothing is selected, fall back to start
-            current.map_or(0, |current| current.saturating_add(1))
+            None => 0,
+            Some(current) if wrap && current >= last => 0,
+            Some(current) => current.saturating_add(1),
+        });
+        self.skip_header(true, &before)
+    }
+
+    /// Handles the page up key.
+    /// Moves up by the amount of items shown on the last render.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn key_page_up(&mut self) -> bool {
+        let page = self.last_rendered_identifiers.len().max(1);
+        self.select_relative(|current| {
+            current.map_or(usize::MAX, |current| current.saturating_sub(page))
         })
     }
 
+    /// Handles the page down key.
+    /// Moves down by the amount of items shown on the last render.
+    ///
+    /// Returns `true` when the selection changed.
+    pub fn key_page_down(&mut self) -> bool {
+        let page = self.last_rendered_identifiers.len().max(1);
+        self.select_relative(|current| current.map_or(0, |current| current.saturating_add(page)))
+    }
+
     /// Handles the left arrow key.
     /// Closes the currently selected or moves to its parent.
     ///
     /// Returns `true` when the selection or the open state changed.
     pub fn key_left(&mut self) -> bool {
-        self.ensure_selected_in_view_on_next_render = true;
-        // Reimplement self.close because of multiple different borrows
-        let mut changed = self.opened.remove(&self.selected);
-        if !changed {
-            // Select the parent by removing the leaf from selection
-            let popped = self.selected.pop();
-            changed = popped.is_some();
+        if let Some(changed) = self.select_visible_instead_of_hidden() {
+            return changed;
         }
-        changed
+        self.ensure_selected_in_view_on_next_render = true;
+        self.undoable(|state| {
+            let selected = state.selected.clone();
+            let mut changed = state.remove_opened(&selected);
+            if !changed {
+                // Select the parent by removing the leaf from selection
+                let popped = state.selected.pop();
+                changed = popped.is_some();
+                // Parents merged into their child by Tree::compact_single_child were not rendered
+                while !state.selected.is_empty()
+                    && !state.last_identifiers.is_empty()
+                    && !state
+                        .last_identifiers
+                        .iter()
+                        .any(|identifier| *identifier == state.selected)
+                {
+                    state.selected.pop();
+                }
+            }
+            changed
+        })
     }
 
     /// Handles the right arrow key.
@@ -356,7 +1355,9 @@ where
     /// Returns `true` when it was closed and has been opened.
     /// Returns `false` when it was already open or nothing being selected.
     pub fn key_right(&mut self) -> bool {
-        if self.selected.is_empty() {
+        if let Some(changed) = self.select_visible_instead_of_hidden() {
+            changed
+        } else if self.selected.is_empty() {
             false
         } else {
             self.ensure_selected_in_view_on_next_render = true;
@@ -364,3 +1365,330 @@ where
         }
     }
 }
+
+/// Whether the identifier is a direct child of the parent identifier
+fn is_child_of<Identifier: PartialEq>(identifier: &[Identifier], parent: &[Identifier]) -> bool {
+    identifier.len() == parent.len() + 1 && identifier.starts_with(parent)
+}
+
+#[test]
+fn open_recursive_with_depth() {
+    let items = vec![TreeItem::new(
+        "a",
+        "A",
+        vec![
+            TreeItem::new(
+                "b",
+                "B",
+                vec![TreeItem::new(
+                    "c",
+                    "C",
+                    vec![TreeItem::new("d", "D", vec![TreeItem::new_leaf("e", "E")]).unwrap()],
+                )
+                .unwrap()],
+            )
+            .unwrap(),
+            TreeItem::new_leaf("f", "F"),
+        ],
+    )
+    .unwrap()];
+
+    let mut state = TreeState::default();
+    assert_eq!(state.open_recursive(&items, &["a"], 2), 2);
+    assert!(state.opened().contains(&["a"]));
+    assert!(state.opened().contains(&["a", "b"]));
+    assert!(!state.opened().contains(&["a", "b", "c"]));
+    assert!(!state.opened().contains(&["a", "f"]));
+    let visible = state
+        .flatten(&items)
+        .into_iter()
+        .map(|flattened| flattened.identifier)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        visible,
+        [
+            vec!["a"],
+            vec!["a", "b"],
+            vec!["a", "b", "c"],
+            vec!["a", "f"]
+        ]
+    );
+
+    assert_eq!(state.open_recursive(&items, &["a"], 2), 0);
+    assert_eq!(state.open_recursive(&items, &["a"], 10), 2);
+}
+
+#[test]
+fn open_recursive_depth_one_is_open() {
+    let items = TreeItem::example();
+    let mut recursive = TreeState::default();
+    let mut plain = TreeState::default();
+    assert_eq!(recursive.open_recursive(&items, &["b"], 1), 1);
+    assert!(plain.open(vec!["b"]));
+    assert_eq!(recursive.opened(), plain.opened());
+    assert_eq!(recursive.open_recursive(&items, &["b"], 1), 0);
+    assert!(!plain.open(vec!["b"]));
+}
+
+#[test]
+fn enter_on_leaf_activates() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    assert_eq!(state.enter(&items), Enter::None);
+
+    state.select(vec!["a"]);
+    assert_eq!(state.enter(&items), Enter::Activated(vec!["a"]));
+    assert!(state.opened().is_empty());
+}
+
+#[test]
+fn enter_on_branch_toggles() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.select(vec!["b"]);
+    assert_eq!(state.enter(&items), Enter::Toggled(true));
+    assert_eq!(state.enter(&items), Enter::Toggled(false));
+    assert!(state.opened().is_empty());
+}
+
+#[test]
+fn enter_behavior() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.select(vec!["b"]);
+
+    state.set_enter_behavior(EnterBehavior::Open);
+    assert_eq!(state.enter(&items), Enter::Toggled(true));
+    assert_eq!(state.enter(&items), Enter::None);
+    assert!(state.opened().contains(&["b"]));
+
+    state.set_enter_behavior(EnterBehavior::ToggleAndActivate);
+    assert_eq!(state.enter(&items), Enter::Activated(vec!["b"]));
+    assert!(state.opened().is_empty());
+}
+
+#[test]
+fn enter_at_max_path_depth_changes_nothing() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["b"]);
+    state.set_max_path_depth(2);
+    state.select(vec!["b", "d"]);
+    assert_eq!(state.enter(&items), Enter::None);
+    assert!(!state.opened().contains(&["b", "d"]));
+
+    state.set_enter_behavior(EnterBehavior::ToggleAndActivate);
+    assert_eq!(state.enter(&items), Enter::None);
+    assert!(!state.opened().contains(&["b", "d"]));
+}
+
+#[test]
+fn select_siblings() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["b"]);
+    state.open(vec!["b", "d"]);
+    state.select(vec!["b", "d"]);
+    assert!(state.select_last_sibling(&items));
+    assert_eq!(state.selected(), ["b", "g"]);
+    assert!(!state.select_last_sibling(&items));
+    assert!(state.select_first_sibling(&items));
+    assert_eq!(state.selected(), ["b", "c"]);
+
+    state.select(vec!["b"]);
+    assert!(state.select_last_sibling(&items));
+    assert_eq!(state.selected(), ["h"]);
+    assert!(state.select_first_sibling(&items));
+    assert_eq!(state.selected(), ["a"]);
+}
+
+#[test]
+fn select_matching_end_on_open_branch() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["b"]);
+    state.open(vec!["b", "d"]);
+
+    state.select(vec!["b"]);
+    assert!(state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b", "g"]);
+    assert!(state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b"]);
+
+    state.select(vec!["b", "d"]);
+    assert!(state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b", "d", "f"]);
+    assert!(state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b", "d"]);
+
+    // Leaf which is not the end of anything
+    state.select(vec!["b", "c"]);
+    assert!(!state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b", "c"]);
+}
+
+#[test]
+fn select_matching_end_on_closed_branch() {
+    let items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.select(vec!["b"]);
+    assert!(!state.select_matching_end(&items));
+    assert_eq!(state.selected(), ["b"]);
+    state.select(vec!["a"]);
+    assert!(!state.select_matching_end(&items));
+}
+
+#[test]
+fn prune_removes_missing() {
+    let mut items = TreeItem::example();
+    let mut state = TreeState::default();
+    state.open(vec!["b"]);
+    state.open(vec!["b", "d"]);
+    state.select(vec!["b", "d", "e"]);
+    state.toggle_bookmark_selected();
+    assert!(!state.prune(&items));
+
+    items[1].children.remove(1);
+    assert!(state.prune(&items));
+    assert_eq!(state.opened().len(), 1);
+    assert!(state.opened().contains(&["b"]));
+    assert_eq!(state.selected(), ["b"]);
+    assert!(state.bookmarks().is_empty());
+}
+
+#[test]
+fn toggle_item_outcomes() {
+    let items = vec![
+        TreeItem::new("b", "Bravo", vec![TreeItem::new_leaf("c", "Charlie")]).unwrap(),
+        TreeItem::new_leaf("e", "Empty").expandable(true),
+        TreeItem::new_lazy("l", "Lazy"),
+    ];
+    let mut state = TreeState::default();
+    assert_eq!(
+        state.toggle_item(&items, vec!["b"]),
+        ToggleOutcome::Toggled(true)
+    );
+    assert_eq!(
+        state.toggle_item(&items, vec!["b"]),
+        ToggleOutcome::Toggled(false)
+    );
+    assert_eq!(
+        state.toggle_item(&items, vec!["b", "c"]),
+        ToggleOutcome::NoChildren(vec!["b", "c"])
+    );
+    state.select(vec!["e"]);
+    assert_eq!(
+        state.toggle_selected_item(&items),
+        ToggleOutcome::NoChildren(vec!["e"])
+    );
+    assert_eq!(
+        state.toggle_item(&items, vec!["l"]),
+        ToggleOutcome::Toggled(true)
+    );
+    assert_eq!(state.toggle_item(&items, vec!["x"]), ToggleOutcome::None);
+    assert_eq!(state.toggle_item(&items, Vec::new()), ToggleOutcome::None);
+    assert_eq!(state.opened().len(), 1);
+}
+
+#[test]
+fn close_and_open_siblings() {
+    let items = vec![
+        TreeItem::new(
+            "p",
+            "Parent",
+            vec![
+                TreeItem::new("a", "Alfa", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+                TreeItem::new("b", "Bravo", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+                TreeItem::new("c", "Charlie", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+                TreeItem::new_leaf("d", "Delta"),
+            ],
+        )
+        .unwrap(),
+        TreeItem::new("q", "Other", vec![TreeItem::new_leaf("x", "X-ray")]).unwrap(),
+    ];
+    let mut state = TreeState::default();
+    state.open(vec!["p"]);
+    state.open(vec!["p", "a"]);
+    state.open(vec!["p", "b"]);
+    state.select(vec!["p", "b"]);
+    assert_eq!(state.close_siblings(&items), 1);
+    assert!(state.opened().contains(&["p", "b"]));
+    assert!(!state.opened().contains(&["p", "a"]));
+    assert_eq!(state.close_siblings(&items), 0);
+
+    assert_eq!(state.open_siblings(&items), 2);
+    assert_eq!(state.opened().len(), 4);
+
+    // Top level
+    state.select(vec!["q"]);
+    assert_eq!(state.open_siblings(&items), 1);
+    assert_eq!(state.close_siblings(&items), 1);
+    assert!(!state.opened().contains(&["p"]));
+    assert!(state.opened().contains(&["q"]));
+
+    state.select(Vec::new());
+    assert_eq!(state.open_siblings(&items), 0);
+}
+
+#[test]
+fn max_path_depth_bounds_stored_paths() {
+    // Every level has a leaf and the next deeper level
+    let mut deepest = TreeItem::new_leaf(10_000, "10000");
+    for depth in (1..10_000).rev() {
+        deepest = TreeItem::new(
+            depth,
+            depth.to_string(),
+            vec![TreeItem::new_leaf(0, "leaf"), deepest],
+        )
+        .unwrap();
+    }
+    let items = vec![deepest];
+
+    let mut state = TreeState::default();
+    state.set_max_path_depth(100);
+    let mut identifier = Vec::new();
+    for depth in 1..10_000 {
+        identifier.push(depth);
+        let opened = state.open(identifier.clone());
+        assert_eq!(opened, depth < 100, "depth {depth}");
+    }
+    assert_eq!(state.opened().len(), 99);
+    // Every opened node shares its parents with the opened parent so only its own identifier is stored
+    assert_eq!(state.opened().node_count(), 99);
+
+    assert!(!state.select(identifier.clone()));
+    assert_eq!(
+        state.try_select(identifier.clone()),
+        Err(TooDeep {
+            max_path_depth: 100
+        })
+    );
+    assert!(state.selected().is_empty());
+    assert_eq!(state.try_select(identifier[..100].to_vec()), Ok(true));
+    assert_eq!(state.try_select(identifier[..100].to_vec()), Ok(false));
+    assert_eq!(
+        state.try_open(identifier[..100].to_vec()),
+        Err(TooDeep {
+            max_path_depth: 100
+        })
+    );
+    assert_eq!(state.try_open(identifier[..99].to_vec()), Ok(false));
+    assert_eq!(
+        state.toggle_item(&items, identifier[..100].to_vec()),
+        ToggleOutcome::TooDeep(identifier[..100].to_vec())
+    );
+    assert_eq!(state.flatten(&items).len(), 99 * 2 + 1);
+
+    state.set_max_path_depth(10);
+    assert_eq!(state.opened().len(), 9);
+    assert_eq!(state.opened().node_count(), 9);
+    assert_eq!(state.selected().len(), 10);
+
+    // Without a limit the stored identifiers still only grow linear with the depth
+    let mut state = TreeState::default();
+    for depth in 1..=1000 {
+        assert!(state.open(identifier[..depth].to_vec()));
+    }
+    assert_eq!(state.opened().len(), 1000);
+    assert_eq!(state.opened().node_count(), 1000);
+}
@@ -0,0 +1,373 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::tree_state::TreeState;
+
+/// Undo related part of the [`TreeState`].
+#[derive(Debug, Clone)]
+pub struct UndoState<Identifier> {
+    capacity: usize,
+    coalesce_selection: bool,
+    /// Currently within an undoable operation, nested operations are part of it
+    recording: bool,
+    /// Nodes opened (`true`) or closed (`false`) by the recorded operation in order
+    opened_log: Vec<(Vec<Identifier>, bool)>,
+    /// Loading marks removed by the recorded operation
+    loading_log: Vec<Vec<Identifier>>,
+    undo: VecDeque<Delta<Identifier>>,
+    redo: Vec<Delta<Identifier>>,
+}
+
+impl<Identifier> Default for UndoState<Identifier> {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            coalesce_selection: true,
+            recording: false,
+            opened_log: Vec::new(),
+            loading_log: Vec::new(),
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+/// Changes restoring the state from before or after an operation.
+#[derive(Debug, Clone)]
+struct Delta<Identifier> {
+    open: Vec<Vec<Identifier>>,
+    close: Vec<Vec<Identifier>>,
+    mark_loading: Vec<Vec<Identifier>>,
+    unmark_loading: Vec<Vec<Identifier>>,
+    selected: Vec<Identifier>,
+    offset: usize,
+    /// Only the selection changed by this delta
+    selection_only: bool,
+}
+
+impl<Identifier> TreeState<Identifier>
+where
+    Identifier: Clone + PartialEq + Eq + core::hash::Hash,
+{
+    /// Set the amount of changes which can be undone with [`undo`](Self::undo).
+    ///
+    /// Opening, closing and selecting items can be undone.
+    /// When more changes happen the oldest one is forgotten.
+    ///
+    /// Defaults to 0 which disables undo.
+    pub fn set_undo_capacity(&mut self, capacity: usize) {
+        self.undo.capacity = capacity;
+        while self.undo.undo.len() > capacity {
+            self.undo.undo.pop_front();
+        }
+        if capacity == 0 {
+            self.undo.redo.clear();
+        }
+    }
+
+    /// Combine consecutive changes of only the selection into one undo step.
+    ///
+    /// This way moving through the items does not fill the [undo](Self::undo) history.
+    ///
+    /// Defaults to `true`.
+    pub const fn set_undo_coalesce_selection(&mut self, coalesce: bool) {
+        self.undo.coalesce_selection = coalesce;
+    }
+
+    /// Restore the opened items, selection and offset from before the last change.
+    /// See [`set_undo_capacity`](Self::set_undo_capacity).
+    ///
+    /// Returns `true` when something was undone.
+    pub fn undo(&mut self) -> bool {
+        let Some(delta) = self.undo.undo.pop_back() else {
+            return false;
+        };
+        let inverse = self.restore(delta);
+        self.undo.redo.push(inverse);
+        true
+    }
+
+    /// Restore what was changed by the last [`undo`](Self::undo).
+    ///
+    /// Returns `true` when something was redone.
+    pub fn redo(&mut self) -> bool {
+        let Some(delta) = self.undo.redo.pop() else {
+            return false;
+        };
+        let inverse = self.restore(delta);
+        self.undo.undo.push_back(inverse);
+        true
+    }
+
+    /// Run the change and remember what it changed to be able to [`undo`](Self::undo) it.
+    pub(super) fn undoable<R>(&mut self, change: impl FnOnce(&mut Self) -> R) -> R {
+        if self.undo.capacity == 0 || self.undo.recording {
+            return change(self);
+        }
+
+        let selected = self.selected.clone();
+        let offset = self.offset;
+        self.undo.recording = true;
+        let result = change(self);
+        self.undo.recording = false;
+
+        let (open, close) = self.take_opened_log();
+        let mark_loading = std::mem::take(&mut self.undo.loading_log);
+        let opened_changed = !open.is_empty() || !close.is_empty() || !mark_loading.is_empty();
+        if !opened_changed && selected == self.selected {
+            return result;
+        }
+        self.undo.redo.clear();
+        let coalesce = self.undo.coalesce_selection
+            && !opened_changed
+            && self
+                .undo
+                .undo
+                .back()
+                .is_some_and(|last| last.selection_only);
+        if !coalesce {
+            self.undo.undo.push_back(Delta {
+                open,
+                close,
+                mark_loading,
+                unmark_loading: Vec::new(),
+                selected,
+                offset,
+                selection_only: !opened_changed,
+            });
+            if self.undo.undo.len() > self.undo.capacity {
+                self.undo.undo.pop_front();
+            }
+        }
+        result
+    }
+
+    /// Nodes to open and to close to get back to before the recorded operation.
+    ///
+    /// Nodes opened and closed again by the operation are not part of it.
+    fn take_opened_log(&mut self) -> (Vec<Vec<Identifier>>, Vec<Vec<Identifier>>) {
+        let log = std::mem::take(&mut self.undo.opened_log);
+        // Only the first change of a node knows its state from before the operation
+        let mut seen = HashSet::new();
+        let changed = log
+            .iter()
+            .map(|(identifier, opened)| {
+                seen.insert(identifier.as_slice()) && self.opened.contains(identifier) == *opened
+            })
+            .collect::<Vec<_>>();
+        drop(seen);
+
+        let mut open = Vec::new();
+        let mut close = Vec::new();
+        for ((identifier, opened), changed) in log.into_iter().zip(changed) {
+            match (changed, opened) {
+                (false, _) => {}
+                (true, true) => close.push(identifier),
+                (true, false) => open.push(identifier),
+            }
+        }
+        (open, close)
+    }
+
+    /// Apply the delta and return the delta reverting it.
+    fn restore(&mut self, delta: Delta<Identifier>) -> Delta<Identifier> {
+        for identifier in &delta.close {
+            self.opened.remove(identifier);
+        }
+        for identifier in &delta.unmark_loading {
+            self.loading.remove(identifier);
+        }
+        let inverse = Delta {
+            open: delta.close,
+            close: delta.open,
+            mark_loading: delta.unmark_loading,
+            unmark_loading: delta.mark_loading,
+            selected: std::mem::replace(&mut self.selected, delta.selected),
+            offset: std::mem::replace(&mut self.offset, delta.offset),
+            selection_only: delta.selection_only,
+        };
+        for identifier in &inverse.close {
+            self.opened.insert(identifier);
+        }
+        self.loading.extend(inverse.unmark_loading.iter().cloned());
+        self.selected_bookmark = None;
+        self.ensure_selected_in_view_on_next_render = false;
+        inverse
+    }
+
+    /// Open the node and remember it for [`undo`](Self::undo).
+    pub(super) fn insert_opened(&mut self, identifier: Vec<Identifier>) -> bool {
+        let inserted = self.opened.insert(&identifier);
+        if inserted && self.undo.recording {
+            self.undo.opened_log.push((identifier, true));
+        }
+        inserted
+    }
+
+    /// Open the child of the opened node `parent` and remember it for [`undo`](Self::undo).
+    ///
+    /// Returns the node of the child and `true` when it was not open before.
+    pub(super) fn insert_opened_child(
+        &mut self,
+        parent: usize,
+        identifier: &Identifier,
+    ) -> (usize, bool) {
+        let (index, inserted) = self.opened.insert_child(parent, identifier);
+        if inserted && self.undo.recording {
+            self.undo
+                .opened_log
+                .push((self.opened.identifier(index), true));
+        }
+        (index, inserted)
+    }
+
+    /// Close the node, forget that it is loading and remember both for [`undo`](Self::undo).
+    pub(super) fn remove_opened(&mut self, identifier: &[Identifier]) -> bool {
+        if self.loading.remove(identifier) && self.undo.recording {
+            self.undo.loading_log.push(identifier.to_vec());
+        }
+        let removed = self.opened.remove(identifier);
+        if removed && self.undo.recording {
+            self.undo.opened_log.push((identifier.to_vec(), false));
+        }
+        removed
+    }
+
+    /// Close the nodes for which `keep` returns `false` and remember them for [`undo`](Self::undo).
+    pub(super) fn retain_opened(&mut self, mut keep: impl FnMut(&[Identifier]) -> bool) {
+        let recording = self.undo.recording;
+        let log = &mut self.undo.opened_log;
+        self.opened.retain(|identifier| {
+            let kept = keep(identifier);
+            if !kept && recording {
+                log.push((identifier.to_vec(), false));
+            }
+            kept
+        });
+    }
+}
+
+#[test]
+fn undo_close_all() {
+    let mut state = TreeState::default();
+    state.set_undo_capacity(10);
+    state.open(vec!["b"]);
+    state.open(vec!["b", "d"]);
+    state.open(vec!["h"]);
+    state.select(vec!["b", "d"]);
+    assert!(state.close_all());
+
+    assert!(state.undo());
+    assert_eq!(state.opened().len(), 3);
+    assert_eq!(state.selected(), ["b", "d"]);
+
+    assert!(state.redo());
+    assert!(state.opened().is_empty());
+    assert!(!state.redo());
+
+    assert!(state.undo());
+    assert!(state.undo());
+    assert_eq!(state.opened().len(), 3);
+    assert!(state.selected().is_empty());
+    assert!(state.undo());
+    assert_eq!(state.opened().len(), 2);
+}
+
+#[test]
+fn undo_coalesces_selection() {
+    let mut state = TreeState::default();
+    state.set_undo_capacity(10);
+    state.open(vec!["b"]);
+    state.select(vec!["a"]);
+    state.select(vec!["b"]);
+    state.select(vec!["b", "c"]);
+    assert!(state.undo());
+    assert!(state.selected().is_empty());
+    assert!(state.undo());
+    assert!(state.opened().is_empty());
+    assert!(!state.undo());
+
+    state.set_undo_coalesce_selection(false);
+    state.select(vec!["a"]);
+    state.select(vec!["b"]);
+    assert!(state.undo());
+    assert_eq!(state.selected(), ["a"]);
+}
+
+#[test]
+fn undo_capacity_drops_oldest() {
+    let mut state = TreeState::default();
+    state.set_undo_capacity(2);
+    state.open(vec!["a"]);
+    state.open(vec!["b"]);
+    state.open(vec!["c"]);
+    assert!(state.undo());
+    assert!(state.undo());
+    assert!(!state.undo());
+    assert_eq!(state.opened().len(), 1);
+    assert!(state.opened().contains(&["a"]));
+}
+
+#[test]
+fn undo_disabled_by_default() {
+    let mut state = TreeState::default();
+    state.open(vec!["a"]);
+    assert!(!state.undo());
+    assert_eq!(state.opened().len(), 1);
+}
+
+#[test]
+fn undo_restores_loading() {
+    let mut state = TreeState::default();
+    state.set_undo_capacity(10);
+    state.open(vec!["i"]);
+    assert!(state.mark_loading(vec!["i"]));
+    assert!(state.close(&["i"]));
+    assert!(state.loading.is_empty());
+
+    assert!(state.undo());
+    assert!(state.opened().contains(&["i"]));
+    assert!(state.loading.contains(&vec!["i"]));
+    assert!(state.redo());
+    assert!(state.opened().is_empty());
+    assert!(state.loading.is_empty());
+}
+
+#[test]
+fn undo_ignores_reverted_changes() {
+    let mut state = TreeState::default();
+    state.set_undo_capacity(10);
+    state.open(vec!["a"]);
+    let items = crate::TreeItem::example();
+    let report = state.apply(
+        &items,
+        [
+            crate::StateOp::Toggle(vec!["b"]),
+            crate::StateOp::Toggle(vec!["b"]),
+        ],
+    );
+    assert!(report.changed);
+    assert!(state.undo());
+    assert!(state.opened().is_empty());
+    assert!(!state.undo());
+}
+
+#[test]
+fn undo_bookmark_jump() {
+    let items = crate::TreeItem::example();
+    let mut state = TreeState::default();
+    state.set_undo_capacity(10);
+    state.select(vec!["b", "d", "e"]);
+    assert!(state.toggle_bookmark_selected());
+    state.selected_bookmark = Some(vec!["b", "d", "e"]);
+    state.selected.clear();
+    assert_eq!(state.enter(&items), crate::Enter::Jumped);
+    assert_eq!(state.opened().len(), 2);
+    assert_eq!(state.selected(), ["b", "d", "e"]);
+
+    assert!(state.undo());
+    assert!(state.opened().is_empty());
+    assert!(state.selected().is_empty());
+    assert!(state.redo());
+    assert_eq!(state.opened().len(), 2);
+    assert_eq!(state.selected(), ["b", "d", "e"]);
+}
@@ -0,0 +1,413 @@
+//! Create [`TreeItem`]s from XML documents.
+//!
+//! Elements become branches, attributes and text content become leafs.
+//! Requires the `xml` feature.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use roxmltree::{Document, ExpandedName, Node};
+
+use crate::tree_item::TreeItem;
+
+/// Error while parsing an XML document.
+pub type XmlError = roxmltree::Error;
+
+/// Identifier of a node in an XML document.
+///
+/// Names are split into their namespace URI and their local name, so equally named nodes of different namespaces are distinct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum XmlSelector {
+    /// Element with its tag name and the zero based occurrence among its siblings with the same namespace and name.
+    Element {
+        namespace: Option<String>,
+        name: String,
+        index: usize,
+    },
+    /// Attribute with the given namespace and name.
+    Attribute {
+        namespace: Option<String>,
+        name: String,
+    },
+    /// Zero based index of the text content among the texts of its parent element.
+    /// Whitespace only texts are ignored.
+    Text(usize),
+}
+
+impl Display for XmlSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Element {
+                namespace,
+                name,
+                index,
+            } => {
+                write_namespace(f, namespace.as_deref())?;
+                write!(f, "{name}[{index}]")
+            }
+            Self::Attribute { namespace, name } => {
+                write!(f, "@")?;
+                write_namespace(f, namespace.as_deref())?;
+                write!(f, "{name}")
+            }
+            Self::Text(index) => write!(f, "#text[{index}]"),
+        }
+    }
+}
+
+/// Namespaces are shown in Clark notation like `{http://www.w3.org}name`
+fn write_namespace(f: &mut std::fmt::Formatter<'_>, namespace: Option<&str>) -> std::fmt::Result {
+    namespace.map_or(Ok(()), |namespace| write!(f, "{{{namespace}}}"))
+}
+
+/// Part of an XML document selected by [`select`].
+#[derive(Debug, Clone, Copy)]
+pub enum XmlValue<'a, 'input> {
+    Element(Node<'a, 'input>),
+    Attribute(&'a str),
+    Text(&'a str),
+}
+
+/// Parse the XML document and create [`TreeItem`]s from it.
+///
+/// # Errors
+///
+/// Errors when the XML is not valid.
+///
+/// # Example
+///
+/// ```
+/// # use tui_tree_widget::xml::{tree_items, XmlSelector};
+/// let items = tree_items(r#"<root><entry id="1">Text</entry></root>"#)?;
+/// assert_eq!(items.len(), 1);
+/// assert_eq!(
+///     items[0].identifier(),
+///     &XmlSelector::Element { namespace: None, name: "root".to_owned(), index: 0 },
+/// );
+/// # Ok::<(), tui_tree_widget::xml::XmlError>(())
+/// ```
+pub fn tree_items(xml: &str) -> Result<Vec<TreeItem<'static, XmlSelector>>, XmlError> {
+    let document = Document::parse(xml)?;
+    let root = document.root_element();
+    let tag_name = root.tag_name();
+    Ok(vec![element_item(
+        root,
+        XmlSelector::Element {
+            namespace: tag_name.namespace().map(ToOwned::to_owned),
+            name: tag_name.name().to_owned(),
+            index: 0,
+        },
+    )])
+}
+
+fn element_item(node: Node, identifier: XmlSelector) -> TreeItem<'static, XmlSelector> {
+    let mut children = node
+        .attributes()
+        .map(|attribute| {
+            let label = qualified_name(node, attribute.namespace(), attribute.name());
+            TreeItem::new_leaf(
+                XmlSelector::Attribute {
+                    namespace: attribute.namespace().map(ToOwned::to_owned),
+                    name: attribute.name().to_owned(),
+                },
+                format!("@{label}={:?}", attribute.value()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut element_counts = HashMap::<(Option<&str>, &str), usize>::new();
+    let mut text_count = 0;
+    for child in node.children() {
+        if child.is_element() {
+            let tag_name = child.tag_name();
+            let count = element_counts
+                .entry((tag_name.namespace(), tag_name.name()))
+                .or_default();
+            let identifier = XmlSelector::Element {
+                namespace: tag_name.namespace().map(ToOwned::to_owned),
+                name: tag_name.name().to_owned(),
+                index: *count,
+            };
+            *count += 1;
+            children.push(element_item(child, identifier));
+        } else if let Some(text) = text_content(child) {
+            children.push(TreeItem::new_leaf(
+                XmlSelector::Text(text_count),
+                text.trim().to_owned(),
+            ));
+            text_count += 1;
+        }
+    }
+
+    // Identifiers are unique among the siblings by construction
+    let tag_name = node.tag_name();
+    let label = qualified_name(node, tag_name.namespace(), tag_name.name());
+    let mut item = TreeItem::new_leaf(identifier, label);
+    item.children = children;
+    item
+}
+
+/// Name with the prefix of its namespace like it is written in the document
+fn qualified_name(node: Node, namespace: Option<&str>, name: &str) -> String {
+    namespace
+        .and_then(|namespace| node.lookup_prefix(namespace))
+        .map_or_else(|| name.to_owned(), |prefix| format!("{prefix}:{name}"))
+}
+
+/// Text of the node when its a text node which is not only whitespace
+fn text_content<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.is_text()
+        .then(|| node.text())
+        .flatten()
+        .filter(|text| !text.trim().is_empty())
+}
+
+/// Get the part of the document the selector path points to.
+///
+/// Returns `None` when the selector path does not exist in the document.
+///
+/// # Example
+///
+/// ```
+/// # use roxmltree::Document;
+/// # use tui_tree_widget::xml::{select, XmlSelector, XmlValue};
+/// let document = Document::parse(r#"<root><entry id="1">Text</entry></root>"#)?;
+/// let selector = [
+///     XmlSelector::Element { namespace: None, name: "root".to_owned(), index: 0 },
+///     XmlSelector::Element { namespace: None, name: "entry".to_owned(), index: 0 },
+///     XmlSelector::Attribute { namespace: None, name: "id".to_owned() },
+/// ];
+/// let Some(XmlValue::Attribute(value)) = select(&document, &selector) else { panic!() };
+/// assert_eq!(value, "1");
+/// # Ok::<(), tui_tree_widget::xml::XmlError>(())
+/// ```
+#[must_use]
+pub fn select<'a, 'input>(
+    document: &'a Document<'input>,
+    selector: &[XmlSelector],
+) -> Option<XmlValue<'a, 'input>> {
+    let (first, rest) = selector.split_first()?;
+    let root = document.root_element();
+    let XmlSelector::Element {
+        namespace,
+        name,
+        index: 0,
+    } = first
+    else {
+        return None;
+    };
+    if !has_name(root, namespace.as_deref(), name) {
+        return None;
+    }
+
+    let mut current = root;
+    for (position, selector) in rest.iter().enumerate() {
+        match selector {
+            XmlSelector::Element {
+                namespace,
+                name,
+                index,
+            } => {
+                current = current
+                    .children()
+                    .filter(|child| {
+                        child.is_element() && has_name(*child, namespace.as_deref(), name)
+                    })
+                    .nth(*index)?;
+            }
+            XmlSelector::Attribute { namespace, name } if position + 1 == rest.len() => {
+                return current
+                    .attribute(expanded_name(namespace.as_deref(), name))
+                    .map(XmlValue::Attribute);
+            }
+            XmlSelector::Text(index) if position + 1 == rest.len() => {
+                return current
+                    .children()
+                    .filter_map(text_content)
+                    .nth(*index)
+                    .map(XmlValue::Text);
+            }
+            XmlSelector::Attribute { .. } | XmlSelector::Text(_) => return None,
+        }
+    }
+    Some(XmlValue::Element(current))
+}
+
+/// Whether the element has exactly this namespace and name.
+///
+/// Unlike [`Node::has_tag_name`] an element with a namespace does not match when no namespace is given.
+fn has_name(node: Node, namespace: Option<&str>, name: &str) -> bool {
+    node.tag_name() == expanded_name(namespace, name)
+}
+
+fn expanded_name<'a>(namespace: Option<&'a str>, name: &'a str) -> ExpandedName<'a, 'a> {
+    namespace.map_or_else(|| name.into(), |namespace| (namespace, name).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"
+        <order id="42" state="open">
+            <item sku="a">Apple</item>
+            <item sku="b">Banana</item>
+            <note>Deliver <b>fast</b> please</note>
+        </order>
+    "#;
+
+    fn element(name: &str, index: usize) -> XmlSelector {
+        XmlSelector::Element {
+            namespace: None,
+            name: name.to_owned(),
+            index,
+        }
+    }
+
+    fn attribute(name: &str) -> XmlSelector {
+        XmlSelector::Attribute {
+            namespace: None,
+            name: name.to_owned(),
+        }
+    }
+
+    fn texts<'a>(items: &'a [TreeItem<'a, XmlSelector>]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| item.text.lines[0].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn builds_items() {
+        let items = tree_items(XML).unwrap();
+        assert_eq!(items.len(), 1);
+        let order = &items[0];
+        assert_eq!(order.identifier, element("order", 0));
+        assert_eq!(
+            texts(&order.children),
+            [r#"@id="42""#, r#"@state="open""#, "item", "item", "note"]
+        );
+        let identifiers = order
+            .children
+            .iter()
+            .map(|item| item.identifier.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            identifiers,
+            ["@id", "@state", "item[0]", "item[1]", "note[0]"]
+        );
+    }
+
+    #[test]
+    fn mixed_content() {
+        let items = tree_items(XML).unwrap();
+        let note = &items[0].children[4];
+        assert_eq!(texts(&note.children), ["Deliver", "b", "please"]);
+        assert_eq!(note.children[0].identifier, XmlSelector::Text(0));
+        assert_eq!(note.children[1].identifier, element("b", 0));
+        assert_eq!(note.children[2].identifier, XmlSelector::Text(1));
+    }
+
+    #[test]
+    fn select_repeated_element() {
+        let document = Document::parse(XML).unwrap();
+        let Some(XmlValue::Element(node)) =
+            select(&document, &[element("order", 0), element("item", 1)])
+        else {
+            panic!("should be an element");
+        };
+        assert_eq!(node.attribute("sku"), Some("b"));
+    }
+
+    #[test]
+    fn select_attribute_and_text() {
+        let document = Document::parse(XML).unwrap();
+        let item = [element("order", 0), element("item", 0)];
+
+        let mut selector = item.to_vec();
+        selector.push(attribute("sku"));
+        assert!(matches!(
+            select(&document, &selector),
+            Some(XmlValue::Attribute("a"))
+        ));
+
+        let mut selector = item.to_vec();
+        selector.push(XmlSelector::Text(0));
+        assert!(matches!(
+            select(&document, &selector),
+            Some(XmlValue::Text("Apple"))
+        ));
+    }
+
+    #[test]
+    fn select_missing_is_none() {
+        let document = Document::parse(XML).unwrap();
+        assert!(select(&document, &[]).is_none());
+        assert!(select(&document, &[element("other", 0)]).is_none());
+        assert!(select(&document, &[element("order", 0), element("item", 2)]).is_none());
+        assert!(select(
+            &document,
+            &[element("order", 0), attribute("id"), element("item", 0)]
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn namespaces_are_distinct() {
+        const SOAP: &str = r#"
+            <s:Envelope xmlns:s="urn:soap" xmlns:n="urn:n" a="1" n:a="2">
+                <s:Body/>
+                <n:Body/>
+                <Body/>
+            </s:Envelope>
+        "#;
+        let items = tree_items(SOAP).unwrap();
+        let envelope = &items[0];
+        assert_eq!(envelope.text.lines[0].to_string(), "s:Envelope");
+        assert_eq!(
+            texts(&envelope.children),
+            [r#"@a="1""#, r#"@n:a="2""#, "s:Body", "n:Body", "Body"]
+        );
+        let identifiers = envelope
+            .children
+            .iter()
+            .map(|item| item.identifier.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            identifiers,
+            [
+                "@a",
+                "@{urn:n}a",
+                "{urn:soap}Body[0]",
+                "{urn:n}Body[0]",
+                "Body[0]"
+            ]
+        );
+
+        let document = Document::parse(SOAP).unwrap();
+        let root = envelope.identifier.clone();
+        let namespaced = XmlSelector::Attribute {
+            namespace: Some("urn:n".to_owned()),
+            name: "a".to_owned(),
+        };
+        assert!(matches!(
+            select(&document, &[root.clone(), namespaced]),
+            Some(XmlValue::Attribute("2"))
+        ));
+        assert!(matches!(
+            select(&document, &[root.clone(), attribute("a")]),
+            Some(XmlValue::Attribute("1"))
+        ));
+        let Some(XmlValue::Element(body)) = select(&document, &[root, element("Body", 0)]) else {
+            panic!("should be an element");
+        };
+        assert_eq!(body.tag_name().namespace(), None);
+        assert!(select(&document, &[element("Envelope", 0)]).is_none());
+    }
+
+    #[test]
+    fn invalid_xml_errors() {
+        assert!(tree_items("<open>").is_err());
+    }
+}